@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{CACHE_CONTROL, VARY},
+        HeaderMap, HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+
+use crate::api::response::JsonResponse;
+use crate::session::session::SessionManager;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Visibility {
+    Public,
+    Private,
+    NoStore,
+}
+
+/// Typed `cache-control` policy for a response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    visibility: Visibility,
+    max_age: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    must_revalidate: bool,
+}
+
+impl CachePolicy {
+    fn of(visibility: Visibility) -> CachePolicy {
+        CachePolicy {
+            visibility,
+            max_age: None,
+            stale_while_revalidate: None,
+            must_revalidate: false,
+        }
+    }
+
+    /// Cacheable by the client and shared caches, such as a CDN.
+    pub fn public() -> CachePolicy {
+        CachePolicy::of(Visibility::Public)
+    }
+
+    /// Cacheable by the client only.
+    pub fn private() -> CachePolicy {
+        CachePolicy::of(Visibility::Private)
+    }
+
+    /// Not stored by any cache.
+    pub fn no_store() -> CachePolicy {
+        CachePolicy::of(Visibility::NoStore)
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+
+    pub fn must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        let mut directives = vec![match self.visibility {
+            Visibility::Public => "public".to_string(),
+            Visibility::Private => "private".to_string(),
+            Visibility::NoStore => "no-store".to_string(),
+        }];
+
+        if self.visibility != Visibility::NoStore {
+            if let Some(max_age) = self.max_age {
+                directives.push(format!("max-age={}", max_age.as_secs()));
+            }
+            if let Some(window) = self.stale_while_revalidate {
+                directives.push(format!("stale-while-revalidate={}", window.as_secs()));
+            }
+            if self.must_revalidate {
+                directives.push("must-revalidate".into());
+            }
+        }
+
+        HeaderValue::from_str(&directives.join(", "))
+            .unwrap_or(HeaderValue::from_static("no-store"))
+    }
+}
+
+impl<T> JsonResponse<T>
+where
+    T: serde::Serialize,
+{
+    pub fn with_cache(self, policy: CachePolicy) -> Self {
+        self.with_header(CACHE_CONTROL, policy.header_value())
+    }
+}
+
+fn directives(headers: &HeaderMap, name: &axum::http::HeaderName) -> Vec<String> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_string())
+        .filter(|directive| !directive.is_empty())
+        .collect()
+}
+
+/// Rewrites `cache-control` to be private, dropping shared-cache directives, unless the response
+/// is already `no-store`.
+fn privatize_cache_control(headers: &mut HeaderMap) {
+    let mut cache_control: Vec<String> = directives(headers, &CACHE_CONTROL)
+        .into_iter()
+        .filter(|directive| {
+            let name = directive.to_ascii_lowercase();
+            name != "public" && !name.starts_with("s-maxage")
+        })
+        .collect();
+
+    let private = cache_control.iter().any(|directive| {
+        let name = directive.to_ascii_lowercase();
+        name == "private" || name == "no-store"
+    });
+    if !private {
+        cache_control.insert(0, "private".into());
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&cache_control.join(", ")) {
+        headers.insert(CACHE_CONTROL, value);
+    }
+}
+
+fn vary_on_credentials(headers: &mut HeaderMap) {
+    vary_on(headers, &["Authorization", "Cookie"]);
+}
+
+/// Adds the request headers to `vary`, keeping the names already listed.
+pub(crate) fn vary_on(headers: &mut HeaderMap, names: &[&str]) {
+    let mut vary = directives(headers, &VARY);
+    if vary.iter().any(|name| name == "*") {
+        return;
+    }
+
+    for name in names {
+        if !vary
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+        {
+            vary.push(name.to_string());
+        }
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&vary.join(", ")) {
+        headers.insert(VARY, value);
+    }
+}
+
+/// Forces `cache-control: private` and `vary: Authorization, Cookie` on responses to requests that
+/// carried a credential or a resolved account, so per-user responses aren't stored by shared
+/// caches.
+pub async fn private_cache<U: Clone + Send + Sync + 'static, S: SessionManager<U>>(
+    State(session_manager): State<S>,
+    cookies: CookieJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authenticated = request.extensions().get::<U>().is_some()
+        || session_manager
+            .extract_credential(&request, &cookies)
+            .is_some();
+
+    let mut response = next.run(request).await;
+
+    if authenticated {
+        privatize_cache_control(response.headers_mut());
+        vary_on_credentials(response.headers_mut());
+    }
+
+    response
+}
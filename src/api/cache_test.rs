@@ -0,0 +1,129 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Router};
+use axum_extra::extract::CookieJar;
+use hyper::header::{CACHE_CONTROL, VARY};
+use tower::util::ServiceExt;
+
+use crate::api::cache::{private_cache, CachePolicy};
+use crate::api::response::{JsonResponse, JsonResult};
+use crate::session::session::{Credential, SessionClaims, SessionManager};
+
+#[derive(Clone)]
+struct FakeAccount {}
+
+struct FakeAppState {}
+
+#[async_trait]
+impl SessionManager<FakeAccount> for Arc<FakeAppState> {
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        Ok(serde_json::from_str(&credential.0)?)
+    }
+
+    async fn get_account(&self, _account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(Some(FakeAccount {}))
+    }
+
+    fn extract_credential(
+        &self,
+        request: &axum::extract::Request,
+        _cookies: &CookieJar,
+    ) -> Option<Credential> {
+        Credential::from_authorization_header(request)
+    }
+}
+
+fn app() -> Router {
+    async fn public_handler() -> JsonResult<&'static str> {
+        JsonResponse::of("cached")
+            .with_cache(
+                CachePolicy::public()
+                    .max_age(Duration::from_secs(60))
+                    .stale_while_revalidate(Duration::from_secs(30)),
+            )
+            .append_header(VARY, "Accept".parse().unwrap())
+            .into()
+    }
+
+    async fn no_store_handler() -> JsonResult<&'static str> {
+        JsonResponse::of("secret")
+            .with_cache(CachePolicy::no_store().max_age(Duration::from_secs(60)))
+            .into()
+    }
+
+    let state = Arc::new(FakeAppState {});
+    Router::new()
+        .route("/public", get(public_handler))
+        .route("/no-store", get(no_store_handler))
+        .layer(from_fn_with_state(
+            state.clone(),
+            private_cache::<FakeAccount, Arc<FakeAppState>>,
+        ))
+        .with_state(state)
+}
+
+fn input(uri: &str, authorization: Option<&str>) -> hyper::Request<axum::body::Body> {
+    let mut request = Request::builder().uri(uri).method(Method::GET);
+    if let Some(authorization) = authorization {
+        request = request.header("authorization", authorization);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[test]
+fn test_cache_policy_header_value() {
+    assert_eq!(
+        CachePolicy::private()
+            .max_age(Duration::from_secs(10))
+            .must_revalidate()
+            .header_value(),
+        "private, max-age=10, must-revalidate"
+    );
+    assert_eq!(
+        CachePolicy::no_store()
+            .max_age(Duration::from_secs(10))
+            .header_value(),
+        "no-store"
+    );
+}
+
+#[tokio::test]
+async fn test_anonymous_response_keeps_policy() {
+    let response = app().oneshot(input("/public", None)).await.unwrap();
+
+    assert_eq!(
+        response.headers()[CACHE_CONTROL],
+        "public, max-age=60, stale-while-revalidate=30"
+    );
+    assert_eq!(response.headers()[VARY], "Accept");
+}
+
+#[tokio::test]
+async fn test_authenticated_response_is_private() {
+    let response = app()
+        .oneshot(input("/public", Some("Bearer token")))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.headers()[CACHE_CONTROL],
+        "private, max-age=60, stale-while-revalidate=30"
+    );
+    assert_eq!(response.headers()[VARY], "Accept, Authorization, Cookie");
+}
+
+#[tokio::test]
+async fn test_authenticated_no_store_response_is_kept() {
+    let response = app()
+        .oneshot(input("/no-store", Some("Bearer token")))
+        .await
+        .unwrap();
+
+    assert_eq!(response.headers()[CACHE_CONTROL], "no-store");
+    assert_eq!(response.headers()[VARY], "Authorization, Cookie");
+}
@@ -0,0 +1,131 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{
+        header::{IF_MATCH, IF_NONE_MATCH},
+        request::Parts,
+        HeaderMap, Method,
+    },
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hyper::StatusCode;
+use sha2::{Digest, Sha256};
+
+use crate::api::response::{JsonResponse, ResponseError};
+
+/// Source of the `etag` of a `JsonResponse`.
+#[derive(Clone, Debug)]
+pub(crate) enum EntityTag {
+    Computed,
+    Version(String),
+}
+
+/// Computes a strong entity tag from the encoded representation of a body.
+pub fn strong_etag(encoded: &[u8]) -> String {
+    format!("\"{}\"", URL_SAFE_NO_PAD.encode(Sha256::digest(encoded)))
+}
+
+/// Formats a caller-provided version, such as a row version or update timestamp, as a strong
+/// entity tag. Bytes not allowed in an entity tag, such as `"` or spaces, are percent-encoded.
+pub fn version_etag(version: &str) -> String {
+    let mut etag = String::with_capacity(version.len() + 2);
+    etag.push('"');
+    for byte in version.bytes() {
+        match byte {
+            b'"' | b'%' => etag.push_str(&format!("%{:02X}", byte)),
+            0x21..=0x7e => etag.push(char::from(byte)),
+            _ => etag.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    etag.push('"');
+    etag
+}
+
+fn matches(header: &str, etag: &str, weak: bool) -> bool {
+    let opaque = |tag: &str| -> Option<String> {
+        match tag.strip_prefix("W/") {
+            Some(tag) if weak => Some(tag.to_string()),
+            Some(_) => None,
+            None => Some(tag.to_string()),
+        }
+    };
+
+    header.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || (opaque(candidate).is_some() && opaque(candidate) == opaque(etag))
+    })
+}
+
+fn header_list(headers: &HeaderMap, name: impl axum::http::header::AsHeaderName) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+
+    match values.is_empty() {
+        true => None,
+        false => Some(values.join(",")),
+    }
+}
+
+/// Extracts the `if-match` and `if-none-match` preconditions of a request.
+#[derive(Clone, Debug)]
+pub struct Preconditions {
+    method: Method,
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+}
+
+impl Preconditions {
+    pub fn of(method: Method, headers: &HeaderMap) -> Preconditions {
+        Preconditions {
+            method,
+            if_match: header_list(headers, IF_MATCH),
+            if_none_match: header_list(headers, IF_NONE_MATCH),
+        }
+    }
+
+    /// Evaluates the preconditions against the current entity tag, returning the status to respond
+    /// with in place of the representation, if any.
+    pub fn evaluate(&self, etag: &str) -> Option<StatusCode> {
+        if let Some(if_match) = &self.if_match {
+            if !matches(if_match, etag, false) {
+                return Some(StatusCode::PRECONDITION_FAILED);
+            }
+        }
+
+        if let Some(if_none_match) = &self.if_none_match {
+            if matches(if_none_match, etag, true) {
+                return match self.method {
+                    Method::GET | Method::HEAD => Some(StatusCode::NOT_MODIFIED),
+                    _ => Some(StatusCode::PRECONDITION_FAILED),
+                };
+            }
+        }
+
+        None
+    }
+
+    /// Checks the preconditions of a write against the current entity tag before performing it,
+    /// rejecting with a 412 status response when they fail.
+    pub fn check(&self, etag: &str) -> Result<(), ResponseError> {
+        match self.evaluate(etag) {
+            Some(StatusCode::PRECONDITION_FAILED) => {
+                Err(JsonResponse::of_status(StatusCode::PRECONDITION_FAILED).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Preconditions
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Preconditions::of(parts.method.clone(), &parts.headers))
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use axum::body::Body;
+use axum::http::{HeaderValue, Method, Request};
+use axum::response::IntoResponse;
+use axum::{routing::MethodRouter, Router};
+use http_body_util::BodyExt;
+use hyper::header::{ETAG, IF_MATCH, IF_NONE_MATCH};
+use hyper::StatusCode;
+use serde::{Serialize, Serializer};
+use tower::util::ServiceExt;
+
+use crate::api::etag::{strong_etag, version_etag, Preconditions};
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+
+fn app() -> Router {
+    async fn get_handler(preconditions: Preconditions) -> JsonResult<Vec<u32>> {
+        JsonResponse::of(vec![1, 2, 3])
+            .with_etag()
+            .with_preconditions(preconditions)
+            .into()
+    }
+
+    async fn put_handler(preconditions: Preconditions) -> JsonResult<JsonStatus> {
+        preconditions.check("\"v2\"")?;
+        JsonResponse::of_status(StatusCode::OK)
+            .with_version("v3")
+            .into()
+    }
+
+    Router::new().route(
+        "/test",
+        MethodRouter::new().get(get_handler).put(put_handler),
+    )
+}
+
+fn input(method: Method, header: Option<(&str, &str)>) -> hyper::Request<axum::body::Body> {
+    let mut request = Request::builder().uri("/test").method(method);
+    if let Some((name, value)) = header {
+        request = request.header(name, value);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+fn expected_etag() -> String {
+    strong_etag(&serde_json::to_vec(&vec![1, 2, 3]).unwrap())
+}
+
+#[tokio::test]
+async fn test_computed_etag_is_set() {
+    let response = app().oneshot(input(Method::GET, None)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ETAG], expected_etag().as_str());
+}
+
+#[tokio::test]
+async fn test_matching_if_none_match_is_not_modified() {
+    let etag = expected_etag();
+    for header in [etag.clone(), format!("W/{}", etag), "\"other\", *".into()] {
+        let response = app()
+            .oneshot(input(Method::GET, Some((IF_NONE_MATCH.as_str(), &header))))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_stale_if_none_match_returns_body() {
+    let response = app()
+        .oneshot(input(
+            Method::GET,
+            Some((IF_NONE_MATCH.as_str(), "\"stale\"")),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "[1,2,3]");
+}
+
+#[tokio::test]
+async fn test_failed_if_match_on_read_is_precondition_failed() {
+    let response = app()
+        .oneshot(input(Method::GET, Some((IF_MATCH.as_str(), "\"stale\""))))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn test_write_with_current_if_match_is_accepted() {
+    let response = app()
+        .oneshot(input(Method::PUT, Some((IF_MATCH.as_str(), "\"v2\""))))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ETAG], "\"v3\"");
+}
+
+#[tokio::test]
+async fn test_write_with_stale_if_match_is_rejected() {
+    for header in ["\"v1\"", "W/\"v2\""] {
+        let response = app()
+            .oneshot(input(Method::PUT, Some((IF_MATCH.as_str(), header))))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: JsonStatus = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, JsonStatus::of(StatusCode::PRECONDITION_FAILED, None));
+    }
+}
+
+#[test]
+fn test_version_etag_escapes_illegal_bytes() {
+    assert_eq!(version_etag("v3"), "\"v3\"");
+    assert_eq!(version_etag("v\"3 %"), "\"v%223%20%25\"");
+    assert!(HeaderValue::from_str(&version_etag("v\"3\n")).is_ok());
+}
+
+#[test]
+fn test_computed_etag_encodes_body_once() {
+    static ENCODED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Serialize for Counted {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            ENCODED.fetch_add(1, Ordering::SeqCst);
+            serializer.serialize_u32(1)
+        }
+    }
+
+    let response = JsonResponse::of(Counted).with_etag().into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ETAG], strong_etag(b"1").as_str());
+    assert_eq!(ENCODED.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::api::request_id::REQUEST_ID_HEADER;
+use crate::api::response::JsonStatus;
+
+pub const ERROR_EXPOSURE_ENV: &str = "OMNIUM_ERROR_EXPOSURE";
+
+/// Controls how much of an internal error is rendered to the caller.
+///
+/// Internal errors are always opaque unless the `expose_errors` middleware is attached with a
+/// policy other than `Opaque`. Intended for development and staging environments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorExposure {
+    #[default]
+    Opaque,
+    Chain,
+    Backtrace,
+}
+
+impl ErrorExposure {
+    pub fn parse(value: &str) -> Option<ErrorExposure> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "opaque" => Some(ErrorExposure::Opaque),
+            "chain" => Some(ErrorExposure::Chain),
+            "backtrace" => Some(ErrorExposure::Backtrace),
+            _ => None,
+        }
+    }
+
+    /// Reads the policy from `OMNIUM_ERROR_EXPOSURE`, falling back to `Opaque` when unset or
+    /// unrecognized.
+    pub fn from_env() -> ErrorExposure {
+        std::env::var(ERROR_EXPOSURE_ENV)
+            .ok()
+            .and_then(|value| ErrorExposure::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Details of an internal error, attached to the extensions of an opaque internal error response.
+#[derive(Clone, Debug)]
+pub struct InternalErrorReport {
+    pub chain: Vec<String>,
+    pub backtrace: Option<String>,
+}
+
+impl InternalErrorReport {
+    pub fn of(err: &anyhow::Error) -> InternalErrorReport {
+        let backtrace = err.backtrace();
+
+        InternalErrorReport {
+            chain: err.chain().map(|cause| cause.to_string()).collect(),
+            backtrace: match backtrace.status() {
+                std::backtrace::BacktraceStatus::Captured => Some(backtrace.to_string()),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn detail(&self, exposure: ErrorExposure) -> Option<String> {
+        match exposure {
+            ErrorExposure::Opaque => None,
+            ErrorExposure::Chain => Some(self.chain.join(": ")),
+            ErrorExposure::Backtrace => match &self.backtrace {
+                Some(backtrace) => Some(format!("{}\n\n{}", self.chain.join(": "), backtrace)),
+                None => Some(self.chain.join(": ")),
+            },
+        }
+    }
+}
+
+pub async fn expose_errors(
+    State(exposure): State<ErrorExposure>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+
+    let detail = response
+        .extensions()
+        .get::<InternalErrorReport>()
+        .and_then(|report| report.detail(exposure));
+
+    match detail {
+        Some(detail) => {
+            let (parts, _) = response.into_parts();
+            let mut body = JsonStatus::of(parts.status, Some(detail));
+            if body.id.is_none() {
+                body.id = parts
+                    .headers
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|header| header.to_str().ok())
+                    .map(String::from);
+            }
+            (parts, Json(body)).into_response()
+        }
+        None => response,
+    }
+}
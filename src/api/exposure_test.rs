@@ -0,0 +1,110 @@
+use anyhow::Context;
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::middleware::from_fn_with_state;
+use axum::{routing::MethodRouter, Router};
+use http_body_util::BodyExt;
+use hyper::StatusCode;
+use tower::util::ServiceExt;
+
+use crate::api::exposure::{expose_errors, ErrorExposure, InternalErrorReport};
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+
+fn input() -> hyper::Request<axum::body::Body> {
+    Request::builder()
+        .uri("/test")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn internal_err_handler() -> JsonResult<()> {
+    Err(anyhow::anyhow!("Connection refused"))
+        .context("Failed to load account")
+        .map_err(Into::into)
+}
+
+async fn client_err_handler() -> JsonResult<()> {
+    Err(JsonResponse::of_status(StatusCode::CONFLICT).into())
+}
+
+fn app(exposure: ErrorExposure) -> Router {
+    Router::new()
+        .route("/test", MethodRouter::new().get(internal_err_handler))
+        .route("/client", MethodRouter::new().get(client_err_handler))
+        .layer(from_fn_with_state(exposure, expose_errors))
+}
+
+async fn status_body(response: axum::response::Response<Body>) -> JsonStatus {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_opaque_hides_internal_err() {
+    let response = app(ErrorExposure::Opaque).oneshot(input()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        status_body(response).await,
+        JsonStatus::of(StatusCode::INTERNAL_SERVER_ERROR, None)
+    );
+}
+
+#[tokio::test]
+async fn test_chain_exposes_internal_err() {
+    let response = app(ErrorExposure::Chain).oneshot(input()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        status_body(response).await,
+        JsonStatus::of(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Some("Failed to load account: Connection refused".into())
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_chain_does_not_alter_status_response() {
+    let response = app(ErrorExposure::Chain)
+        .oneshot(
+            Request::builder()
+                .uri("/client")
+                .method(Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        status_body(response).await,
+        JsonStatus::of(StatusCode::CONFLICT, None)
+    );
+}
+
+#[test]
+fn test_report_detail_without_captured_backtrace() {
+    let report = InternalErrorReport {
+        chain: vec!["outer".into(), "inner".into()],
+        backtrace: None,
+    };
+
+    assert_eq!(report.detail(ErrorExposure::Opaque), None);
+    assert_eq!(
+        report.detail(ErrorExposure::Backtrace),
+        Some("outer: inner".into())
+    );
+}
+
+#[test]
+fn test_parse_exposure() {
+    assert_eq!(ErrorExposure::parse("Chain"), Some(ErrorExposure::Chain));
+    assert_eq!(
+        ErrorExposure::parse(" backtrace "),
+        Some(ErrorExposure::Backtrace)
+    );
+    assert_eq!(ErrorExposure::parse("verbose"), None);
+}
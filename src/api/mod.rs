@@ -1,3 +1,35 @@
+pub mod cache;
+#[cfg(test)]
+mod cache_test;
+pub mod etag;
+#[cfg(test)]
+mod etag_test;
+pub mod exposure;
+#[cfg(test)]
+mod exposure_test;
+pub mod negotiate;
+#[cfg(test)]
+mod negotiate_test;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+#[cfg(all(test, feature = "openapi"))]
+mod openapi_test;
+pub mod page;
+#[cfg(test)]
+mod page_test;
+pub mod request_id;
+#[cfg(test)]
+mod request_id_test;
 pub mod response;
 #[cfg(test)]
 mod response_test;
+#[cfg(feature = "ws")]
+pub mod socket;
+#[cfg(all(test, feature = "ws"))]
+mod socket_test;
+pub mod sse;
+#[cfg(test)]
+mod sse_test;
+pub mod stream;
+#[cfg(test)]
+mod stream_test;
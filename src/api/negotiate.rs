@@ -0,0 +1,146 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header::ACCEPT, request::Parts, HeaderMap},
+};
+use hyper::StatusCode;
+use serde::Serialize;
+
+use crate::api::response::{JsonResponse, JsonStatus};
+
+/// Serialization format of a `JsonResponse` body. Variants depend on the enabled features.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl Encoding {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Encoding::Cbor => "application/cbor",
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, body: &T) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Encoding::Json => Ok(serde_json::to_vec(body)?),
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => Ok(rmp_serde::to_vec_named(body)?),
+            #[cfg(feature = "cbor")]
+            Encoding::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(body, &mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    const SUPPORTED: &[Encoding] = &[
+        Encoding::Json,
+        #[cfg(feature = "msgpack")]
+        Encoding::MessagePack,
+        #[cfg(feature = "cbor")]
+        Encoding::Cbor,
+    ];
+
+    /// How specifically a media range matches the encoding: 2 for its own media type, 1 for
+    /// `application/*` and 0 for `*/*`. Wildcards only match `Json`, the default encoding.
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        match (self, media_type) {
+            (Encoding::Json, "application/json") => Some(2),
+            (Encoding::Json, "application/*") => Some(1),
+            (Encoding::Json, "*/*") => Some(0),
+            #[cfg(feature = "msgpack")]
+            (
+                Encoding::MessagePack,
+                "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack",
+            ) => Some(2),
+            #[cfg(feature = "cbor")]
+            (Encoding::Cbor, "application/cbor") => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Selects the encoding with the highest quality value from an `accept` header, preferring
+    /// earlier media ranges on ties. Each encoding takes the quality of its most specific media
+    /// range, so `application/json;q=0` excludes JSON even alongside `*/*`. Returns `Json` when the
+    /// header is absent.
+    pub fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+        let mut accepted = headers.get_all(ACCEPT).iter().peekable();
+        if accepted.peek().is_none() {
+            return Some(Encoding::Json);
+        }
+
+        let media_ranges: Vec<(String, f32)> = accepted
+            .filter_map(|header| header.to_str().ok())
+            .flat_map(|header| header.split(','))
+            .map(|media_range| {
+                let mut params = media_range.split(';').map(str::trim);
+                let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+                // Quality values outside 0..=1, or NaN, are invalid and exclude the media range.
+                let quality = params
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+                    .filter(|q| (0.0..=1.0).contains(q))
+                    .unwrap_or(0.0);
+                (media_type, quality)
+            })
+            .collect();
+
+        let mut selected: Option<(Encoding, f32, usize)> = None;
+
+        for encoding in Encoding::SUPPORTED {
+            let matched = media_ranges
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (media_type, quality))| {
+                    encoding
+                        .specificity(media_type)
+                        .map(|specificity| (specificity, index, *quality))
+                })
+                .min_by_key(|(specificity, index, _)| (u8::MAX - specificity, *index));
+
+            let Some((_, index, quality)) = matched else {
+                continue;
+            };
+            if quality <= 0.0 {
+                continue;
+            }
+
+            if selected.is_none_or(|(_, best, best_index)| {
+                quality > best || (quality == best && index < best_index)
+            }) {
+                selected = Some((*encoding, quality, index));
+            }
+        }
+
+        selected.map(|(encoding, _, _)| encoding)
+    }
+}
+
+/// Extracts the negotiated `Encoding` from the `accept` header, rejecting with a 406 status
+/// response when no supported encoding is acceptable.
+#[derive(Clone, Copy, Debug)]
+pub struct Accept(pub Encoding);
+
+impl<S> FromRequestParts<S> for Accept
+where
+    S: Send + Sync,
+{
+    type Rejection = JsonResponse<JsonStatus>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Encoding::negotiate(&parts.headers)
+            .map(Accept)
+            .ok_or_else(|| JsonResponse::of_status(StatusCode::NOT_ACCEPTABLE))
+    }
+}
@@ -0,0 +1,180 @@
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Method, Request};
+use axum::{routing::MethodRouter, Router};
+use http_body_util::BodyExt;
+use hyper::header::{ACCEPT, CONTENT_TYPE, VARY};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use tower::util::ServiceExt;
+
+use crate::api::negotiate::{Accept, Encoding};
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    name: String,
+    count: u32,
+}
+
+fn app() -> Router {
+    async fn handler(Accept(encoding): Accept) -> JsonResult<Item> {
+        JsonResponse::of(Item {
+            name: "test".into(),
+            count: 3,
+        })
+        .with_encoding(encoding)
+        .into()
+    }
+
+    Router::new().route("/test", MethodRouter::new().get(handler))
+}
+
+fn input(accept: Option<&str>) -> hyper::Request<axum::body::Body> {
+    let mut request = Request::builder().uri("/test").method(Method::GET);
+    if let Some(accept) = accept {
+        request = request.header(ACCEPT, accept);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+fn accept(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static(value));
+    headers
+}
+
+#[test]
+fn test_negotiate_defaults_to_json() {
+    assert_eq!(Encoding::negotiate(&HeaderMap::new()), Some(Encoding::Json));
+    assert_eq!(Encoding::negotiate(&accept("*/*")), Some(Encoding::Json));
+    assert_eq!(
+        Encoding::negotiate(&accept("text/html, application/*;q=0.5")),
+        Some(Encoding::Json)
+    );
+}
+
+#[test]
+fn test_negotiate_rejects_unsupported_and_zero_quality() {
+    assert_eq!(Encoding::negotiate(&accept("text/html")), None);
+    assert_eq!(Encoding::negotiate(&accept("application/json;q=0")), None);
+}
+
+#[test]
+fn test_negotiate_prefers_explicit_media_type_over_wildcard() {
+    assert_eq!(
+        Encoding::negotiate(&accept("application/json;q=0, */*")),
+        None
+    );
+    assert_eq!(
+        Encoding::negotiate(&accept("*/*, application/json;q=0")),
+        None
+    );
+    assert_eq!(
+        Encoding::negotiate(&accept("*/*;q=0, application/json;q=0.2")),
+        Some(Encoding::Json)
+    );
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_negotiate_falls_back_from_excluded_json() {
+    assert_eq!(
+        Encoding::negotiate(&accept(
+            "application/json;q=0, */*, application/msgpack;q=0.1"
+        )),
+        Some(Encoding::MessagePack)
+    );
+}
+
+#[test]
+fn test_negotiate_excludes_invalid_quality() {
+    for value in [
+        "application/json;q=NaN",
+        "application/json;q=2",
+        "application/json;q=-1",
+        "application/json;q=high",
+    ] {
+        assert_eq!(Encoding::negotiate(&accept(value)), None, "{}", value);
+    }
+    assert_eq!(
+        Encoding::negotiate(&accept("application/json;q=1.5, */*;q=0.1")),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_json_is_negotiated() {
+    let response = app()
+        .oneshot(input(Some("application/json")))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(response.headers()[VARY], "Accept");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Item = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        Item {
+            name: "test".into(),
+            count: 3,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_unacceptable_is_rejected() {
+    let response = app().oneshot(input(Some("text/html"))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: JsonStatus = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, JsonStatus::of(StatusCode::NOT_ACCEPTABLE, None));
+}
+
+#[cfg(feature = "msgpack")]
+#[tokio::test]
+async fn test_msgpack_is_negotiated() {
+    let response = app()
+        .oneshot(input(Some("application/json;q=0.5, application/msgpack")))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/msgpack");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Item = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        Item {
+            name: "test".into(),
+            count: 3,
+        }
+    );
+}
+
+#[cfg(feature = "cbor")]
+#[tokio::test]
+async fn test_cbor_is_negotiated() {
+    let response = app()
+        .oneshot(input(Some("application/cbor")))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/cbor");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Item = ciborium::from_reader(body.as_ref()).unwrap();
+    assert_eq!(
+        body,
+        Item {
+            name: "test".into(),
+            count: 3,
+        }
+    );
+}
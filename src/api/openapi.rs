@@ -0,0 +1,310 @@
+use std::future::Future;
+
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{on, MethodFilter},
+    Json, Router,
+};
+use schemars::{generate::SchemaSettings, JsonSchema, SchemaGenerator};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+use crate::session::session::{authorize, SessionManager};
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+const BEARER_SCHEME: &str = "bearerAuth";
+
+const COOKIE_SCHEME: &str = "cookieAuth";
+
+/// A handler return type that can describe its responses.
+pub trait DocumentedResponse {
+    fn responses(generator: &mut SchemaGenerator) -> Map<String, Value>;
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn status_response(generator: &mut SchemaGenerator, description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": json_content(generator.subschema_for::<JsonStatus>().to_value()),
+    })
+}
+
+impl<T> DocumentedResponse for JsonResponse<T>
+where
+    T: Serialize + JsonSchema,
+{
+    fn responses(generator: &mut SchemaGenerator) -> Map<String, Value> {
+        let mut responses = Map::new();
+        responses.insert(
+            "200".into(),
+            json!({
+                "description": "Success",
+                "content": json_content(generator.subschema_for::<T>().to_value()),
+            }),
+        );
+        responses.insert("default".into(), status_response(generator, "Error"));
+        responses
+    }
+}
+
+impl<T> DocumentedResponse for JsonResult<T>
+where
+    T: Serialize + JsonSchema,
+{
+    fn responses(generator: &mut SchemaGenerator) -> Map<String, Value> {
+        JsonResponse::<T>::responses(generator)
+    }
+}
+
+/// A handler function whose return type can describe its responses, implemented for async
+/// functions of up to eight extractors returning a `DocumentedResponse`.
+pub trait DocumentedHandler<Args> {
+    fn responses(generator: &mut SchemaGenerator) -> Map<String, Value>;
+}
+
+macro_rules! impl_documented_handler {
+    ($($arg:ident),*) => {
+        impl<F, Fut, $($arg,)*> DocumentedHandler<($($arg,)*)> for F
+        where
+            F: FnOnce($($arg,)*) -> Fut,
+            Fut: Future,
+            Fut::Output: DocumentedResponse,
+        {
+            fn responses(generator: &mut SchemaGenerator) -> Map<String, Value> {
+                <Fut::Output as DocumentedResponse>::responses(generator)
+            }
+        }
+    };
+}
+
+impl_documented_handler!();
+impl_documented_handler!(T1);
+impl_documented_handler!(T1, T2);
+impl_documented_handler!(T1, T2, T3);
+impl_documented_handler!(T1, T2, T3, T4);
+impl_documented_handler!(T1, T2, T3, T4, T5);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// An HTTP method of an OpenAPI operation, each supported by the `Router`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiMethod {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Options,
+    Head,
+    Patch,
+    Trace,
+}
+
+impl ApiMethod {
+    /// The method as the key of its operation in an OpenAPI path item, such as `get`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiMethod::Get => "get",
+            ApiMethod::Put => "put",
+            ApiMethod::Post => "post",
+            ApiMethod::Delete => "delete",
+            ApiMethod::Options => "options",
+            ApiMethod::Head => "head",
+            ApiMethod::Patch => "patch",
+            ApiMethod::Trace => "trace",
+        }
+    }
+
+    fn filter(&self) -> MethodFilter {
+        match self {
+            ApiMethod::Get => MethodFilter::GET,
+            ApiMethod::Put => MethodFilter::PUT,
+            ApiMethod::Post => MethodFilter::POST,
+            ApiMethod::Delete => MethodFilter::DELETE,
+            ApiMethod::Options => MethodFilter::OPTIONS,
+            ApiMethod::Head => MethodFilter::HEAD,
+            ApiMethod::Patch => MethodFilter::PATCH,
+            ApiMethod::Trace => MethodFilter::TRACE,
+        }
+    }
+}
+
+/// A `Router` that documents its routes in an OpenAPI 3.1 document as they are added.
+pub struct ApiRouter<S = ()> {
+    router: Router<S>,
+    generator: SchemaGenerator,
+    info: Value,
+    paths: Map<String, Value>,
+    session_cookie: Option<String>,
+    spec_path: Option<String>,
+}
+
+impl<S> ApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new(title: &str, version: &str) -> ApiRouter<S> {
+        let settings = SchemaSettings::draft2020_12()
+            .with(|settings| {
+                settings.definitions_path = "/components/schemas".into();
+                settings.meta_schema = None;
+            })
+            .for_serialize();
+
+        ApiRouter {
+            router: Router::new(),
+            generator: settings.into_generator(),
+            info: json!({ "title": title, "version": version }),
+            paths: Map::new(),
+            session_cookie: None,
+            spec_path: None,
+        }
+    }
+
+    /// Documents the session cookie as an alternative to the `authorization` header on routes
+    /// layered with `authorize`.
+    pub fn with_session_cookie(mut self, cookie_name: &str) -> Self {
+        self.session_cookie = Some(cookie_name.into());
+        self
+    }
+
+    /// Serves the OpenAPI document as JSON at the given path.
+    pub fn with_spec_route(mut self, path: &str) -> Self {
+        self.spec_path = Some(path.into());
+        self
+    }
+
+    pub fn route<H, T, A>(mut self, path: &str, method: ApiMethod, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        let responses = H::responses(&mut self.generator);
+        let operations = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(operations) = operations.as_object_mut() {
+            operations.insert(method.as_str().into(), json!({ "responses": responses }));
+        }
+
+        self.router = self.router.route(path, on(method.filter(), handler));
+        self
+    }
+
+    pub fn get<H, T, A>(self, path: &str, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        self.route(path, ApiMethod::Get, handler)
+    }
+
+    pub fn post<H, T, A>(self, path: &str, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        self.route(path, ApiMethod::Post, handler)
+    }
+
+    pub fn put<H, T, A>(self, path: &str, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        self.route(path, ApiMethod::Put, handler)
+    }
+
+    pub fn patch<H, T, A>(self, path: &str, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        self.route(path, ApiMethod::Patch, handler)
+    }
+
+    pub fn delete<H, T, A>(self, path: &str, handler: H) -> Self
+    where
+        H: axum::handler::Handler<T, S> + DocumentedHandler<A>,
+        T: 'static,
+    {
+        self.route(path, ApiMethod::Delete, handler)
+    }
+
+    /// Layers `authorize` over the routes added so far, documenting a bearer or session cookie
+    /// security requirement and an unauthorized response on each of their operations.
+    pub fn authorize<U, M>(mut self, session_manager: M) -> Self
+    where
+        U: Clone + Send + Sync + 'static,
+        M: SessionManager<U> + Clone + Send + Sync + 'static,
+    {
+        let mut security = vec![json!({ BEARER_SCHEME: [] })];
+        if self.session_cookie.is_some() {
+            security.push(json!({ COOKIE_SCHEME: [] }));
+        }
+        let unauthorized = status_response(&mut self.generator, "Unauthorized");
+
+        for operation in self
+            .paths
+            .values_mut()
+            .filter_map(Value::as_object_mut)
+            .flat_map(|operations| operations.values_mut())
+            .filter_map(Value::as_object_mut)
+        {
+            operation.insert("security".into(), Value::Array(security.clone()));
+            if let Some(responses) = operation
+                .get_mut("responses")
+                .and_then(Value::as_object_mut)
+            {
+                responses.insert("401".into(), unauthorized.clone());
+            }
+        }
+
+        self.router = self
+            .router
+            .layer(from_fn_with_state(session_manager, authorize::<U, M>));
+        self
+    }
+
+    /// Builds the OpenAPI document for the routes added so far.
+    pub fn openapi(&self) -> Value {
+        let mut security_schemes = Map::new();
+        security_schemes.insert(
+            BEARER_SCHEME.into(),
+            json!({ "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }),
+        );
+        if let Some(cookie_name) = &self.session_cookie {
+            security_schemes.insert(
+                COOKIE_SCHEME.into(),
+                json!({ "type": "apiKey", "in": "cookie", "name": cookie_name }),
+            );
+        }
+
+        json!({
+            "openapi": OPENAPI_VERSION,
+            "info": self.info,
+            "paths": self.paths,
+            "components": {
+                "schemas": self.generator.definitions(),
+                "securitySchemes": security_schemes,
+            },
+        })
+    }
+
+    pub fn into_router(self) -> Router<S> {
+        match &self.spec_path {
+            Some(spec_path) => {
+                let spec = self.openapi();
+                self.router
+                    .route(spec_path, axum::routing::get(|| async move { Json(spec) }))
+            }
+            None => self.router,
+        }
+    }
+}
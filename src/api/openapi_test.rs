@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::{Method, Request};
+use axum::Router;
+use axum_extra::extract::CookieJar;
+use http_body_util::BodyExt;
+use hyper::StatusCode;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use crate::api::openapi::{ApiMethod, ApiRouter};
+use crate::api::page::Page;
+use crate::api::response::{JsonResponse, JsonResult};
+use crate::session::session::{Credential, SessionClaims, SessionManager};
+
+#[derive(Clone)]
+struct FakeAccount {}
+
+#[derive(Clone)]
+struct FakeAppState {}
+
+#[async_trait]
+impl SessionManager<FakeAccount> for Arc<FakeAppState> {
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        Ok(serde_json::from_str(&credential.0)?)
+    }
+
+    async fn get_account(&self, _account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(Some(FakeAccount {}))
+    }
+
+    fn extract_credential(
+        &self,
+        request: &axum::extract::Request,
+        _cookies: &CookieJar,
+    ) -> Option<Credential> {
+        Credential::from_authorization_header(request)
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Item {
+    name: String,
+}
+
+async fn list_items() -> JsonResult<Page<Item>> {
+    JsonResponse::of(Page::of(vec![], None)).into()
+}
+
+async fn get_item(Path(name): Path<String>) -> JsonResult<Item> {
+    JsonResponse::of(Item { name }).into()
+}
+
+async fn health() -> JsonResult<String> {
+    JsonResponse::of("ok".to_string()).into()
+}
+
+fn api() -> ApiRouter {
+    ApiRouter::new("Test API", "1.0.0")
+        .with_session_cookie("__Host-session")
+        .with_spec_route("/openapi.json")
+        .get("/items", list_items)
+        .get("/items/{name}", get_item)
+        .authorize::<FakeAccount, _>(Arc::new(FakeAppState {}))
+        .get("/health", health)
+}
+
+fn input(uri: &str) -> hyper::Request<axum::body::Body> {
+    Request::builder()
+        .uri(uri)
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[test]
+fn test_openapi_documents_responses_and_security() {
+    let spec = api().openapi();
+
+    assert_eq!(spec["openapi"], "3.1.0");
+    assert_eq!(
+        spec["info"],
+        json!({ "title": "Test API", "version": "1.0.0" })
+    );
+
+    let get_item = &spec["paths"]["/items/{name}"]["get"];
+    assert_eq!(
+        get_item["responses"]["200"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Item" })
+    );
+    assert_eq!(
+        get_item["responses"]["default"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/JsonStatus" })
+    );
+    assert!(get_item["responses"]["401"].is_object());
+    assert_eq!(
+        get_item["security"],
+        json!([{ "bearerAuth": [] }, { "cookieAuth": [] }])
+    );
+
+    let health = &spec["paths"]["/health"]["get"];
+    assert_eq!(
+        health["responses"]["200"]["content"]["application/json"]["schema"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(health["security"], Value::Null);
+
+    let schemas = &spec["components"]["schemas"];
+    assert!(schemas["Item"].is_object());
+    assert!(schemas["JsonStatus"].is_object());
+    assert!(schemas["Page"].is_object());
+    assert_eq!(
+        spec["components"]["securitySchemes"]["cookieAuth"],
+        json!({ "type": "apiKey", "in": "cookie", "name": "__Host-session" })
+    );
+}
+
+#[tokio::test]
+async fn test_spec_is_served_and_routes_are_authorized() {
+    let app: Router = api().into_router();
+
+    let response = app.clone().oneshot(input("/openapi.json")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let spec: Value = serde_json::from_slice(&body).unwrap();
+    assert!(spec["paths"]["/items"]["get"].is_object());
+
+    let response = app.clone().oneshot(input("/items")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.oneshot(input("/health")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_route_documents_and_serves_method() {
+    let api = ApiRouter::<()>::new("Test API", "1.0.0").route("/items", ApiMethod::Options, health);
+
+    let spec = api.openapi();
+    assert!(spec["paths"]["/items"]["options"].is_object());
+
+    let request = Request::builder()
+        .uri("/items")
+        .method(Method::OPTIONS)
+        .body(Body::empty())
+        .unwrap();
+    let response = api.into_router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::{header::LINK, request::Parts, HeaderValue, Uri},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::Mac;
+use hyper::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::api::response::{JsonResponse, JsonStatus};
+use crate::session::token::sign;
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 25;
+
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Body of a page of a list endpoint, usable with `JsonResponse::of`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+impl<T> Page<T> {
+    pub fn of(items: Vec<T>, next_cursor: Option<String>) -> Page<T> {
+        Page {
+            items,
+            next_cursor,
+            total: None,
+        }
+    }
+
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+}
+
+/// Pagination settings, implemented on the application state. Cursors are signed with the cursor
+/// key, so clients can't forge them.
+pub trait Paginator {
+    fn cursor_key(&self) -> &[u8];
+
+    fn default_limit(&self) -> u32 {
+        DEFAULT_PAGE_LIMIT
+    }
+
+    fn max_limit(&self) -> u32 {
+        MAX_PAGE_LIMIT
+    }
+
+    fn encode_cursor<C: Serialize>(&self, cursor: &C) -> anyhow::Result<String> {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor)?);
+        let signature =
+            URL_SAFE_NO_PAD.encode(sign(self.cursor_key(), &payload)?.finalize().into_bytes());
+        Ok(format!("{}.{}", payload, signature))
+    }
+
+    fn decode_cursor<C: DeserializeOwned>(&self, cursor: &str) -> anyhow::Result<C> {
+        let (payload, signature) = cursor
+            .split_once('.')
+            .ok_or_else(|| anyhow::anyhow!("Malformed cursor"))?;
+
+        sign(self.cursor_key(), payload)?.verify_slice(&URL_SAFE_NO_PAD.decode(signature)?)?;
+
+        Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)?)
+    }
+}
+
+#[derive(Deserialize)]
+struct PaginationQuery {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+/// Extracts a validated `limit` and decoded `cursor` from the query, rejecting with a 400 status
+/// response when either is invalid.
+#[derive(Debug)]
+pub struct Pagination<C> {
+    pub limit: u32,
+    pub cursor: Option<C>,
+}
+
+impl<C, S> FromRequestParts<S> for Pagination<C>
+where
+    C: DeserializeOwned,
+    S: Paginator + Send + Sync,
+{
+    type Rejection = JsonResponse<JsonStatus>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<PaginationQuery>::try_from_uri(&parts.uri).map_err(|err| {
+            JsonResponse::of_client_err(err.into(), StatusCode::BAD_REQUEST)
+                .with_detail("Invalid pagination query.")
+        })?;
+
+        let limit = query
+            .limit
+            .unwrap_or(state.default_limit().min(state.max_limit()));
+        if limit == 0 || limit > state.max_limit() {
+            return Err(
+                JsonResponse::of_status(StatusCode::BAD_REQUEST).with_detail(format!(
+                    "Limit must be between 1 and {}.",
+                    state.max_limit()
+                )),
+            );
+        }
+
+        let cursor = match query.cursor {
+            Some(cursor) => Some(state.decode_cursor(&cursor).map_err(|err| {
+                JsonResponse::of_client_err(err, StatusCode::BAD_REQUEST)
+                    .with_detail("Invalid cursor.")
+            })?),
+            None => None,
+        };
+
+        Ok(Pagination { limit, cursor })
+    }
+}
+
+impl<T> JsonResponse<Page<T>>
+where
+    T: Serialize,
+{
+    /// Appends a `link` header to the next page, when there is one, preserving the request's other
+    /// query parameters.
+    pub fn with_next_link(self, uri: &Uri, limit: u32) -> Self {
+        let Some(next_cursor) = self.body().next_cursor.clone() else {
+            return self;
+        };
+
+        let mut query: Vec<String> = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .filter(|param| !param.starts_with("cursor=") && !param.starts_with("limit="))
+            .map(String::from)
+            .collect();
+        query.push(format!("limit={}", limit));
+        query.push(format!("cursor={}", next_cursor));
+
+        let link = format!("<{}?{}>; rel=\"next\"", uri.path(), query.join("&"));
+
+        match HeaderValue::from_str(&link) {
+            Ok(link) => self.append_header(LINK, link),
+            Err(_) => self,
+        }
+    }
+}
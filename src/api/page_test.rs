@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::OriginalUri;
+use axum::http::{Method, Request};
+use axum::{routing::get, Router};
+use http_body_util::BodyExt;
+use hyper::header::LINK;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use tower::util::ServiceExt;
+
+use crate::api::page::{Page, Pagination, Paginator};
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+
+struct FakeAppState {}
+
+impl Paginator for Arc<FakeAppState> {
+    fn cursor_key(&self) -> &[u8] {
+        b"test-cursor-key"
+    }
+
+    fn max_limit(&self) -> u32 {
+        10
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ItemCursor {
+    after: u32,
+}
+
+const TOTAL: u32 = 12;
+
+async fn handler(
+    axum::extract::State(state): axum::extract::State<Arc<FakeAppState>>,
+    OriginalUri(uri): OriginalUri,
+    pagination: Pagination<ItemCursor>,
+) -> JsonResult<Page<u32>> {
+    let start = pagination.cursor.map_or(1, |cursor| cursor.after + 1);
+    let end = (start + pagination.limit - 1).min(TOTAL);
+    let items: Vec<u32> = (start..=end).collect();
+
+    let next_cursor = match end < TOTAL {
+        true => Some(state.encode_cursor(&ItemCursor { after: end })?),
+        false => None,
+    };
+
+    JsonResponse::of(Page::of(items, next_cursor).with_total(TOTAL.into()))
+        .with_next_link(&uri, pagination.limit)
+        .into()
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/items", get(handler))
+        .with_state(Arc::new(FakeAppState {}))
+}
+
+fn input(uri: &str) -> hyper::Request<axum::body::Body> {
+    Request::builder()
+        .uri(uri)
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn page_body(response: axum::response::Response<Body>) -> Page<u32> {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn status_body(response: axum::response::Response<Body>) -> JsonStatus {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_pages_follow_next_cursor() {
+    let response = app()
+        .oneshot(input("/items?limit=5&sort=asc"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let link = response.headers()[LINK].to_str().unwrap().to_string();
+    let page = page_body(response).await;
+
+    assert_eq!(page.items, vec![1, 2, 3, 4, 5]);
+    assert_eq!(page.total, Some(12));
+    let next_cursor = page.next_cursor.unwrap();
+    assert_eq!(
+        link,
+        format!(
+            "</items?sort=asc&limit=5&cursor={}>; rel=\"next\"",
+            next_cursor
+        )
+    );
+
+    let response = app()
+        .oneshot(input(&format!("/items?limit=10&cursor={}", next_cursor)))
+        .await
+        .unwrap();
+
+    assert!(response.headers().get(LINK).is_none());
+
+    let page = page_body(response).await;
+    assert_eq!(page.items, vec![6, 7, 8, 9, 10, 11, 12]);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[tokio::test]
+async fn test_limit_out_of_range_is_rejected() {
+    for uri in ["/items?limit=0", "/items?limit=11", "/items?limit=many"] {
+        let response = app().oneshot(input(uri)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let response = app().oneshot(input("/items?limit=11")).await.unwrap();
+    assert_eq!(
+        status_body(response).await,
+        JsonStatus::of(
+            StatusCode::BAD_REQUEST,
+            Some("Limit must be between 1 and 10.".into())
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_forged_cursor_is_rejected() {
+    let state = Arc::new(FakeAppState {});
+    let cursor = state.encode_cursor(&ItemCursor { after: 5 }).unwrap();
+    let (_, signature) = cursor.split_once('.').unwrap();
+    let forged = format!("{}.{}", "eyJhZnRlciI6MTB9", signature);
+
+    let response = app()
+        .oneshot(input(&format!("/items?cursor={}", forged)))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        status_body(response).await,
+        JsonStatus::of(StatusCode::BAD_REQUEST, Some("Invalid cursor.".into()))
+    );
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let state = Arc::new(FakeAppState {});
+    let cursor = state.encode_cursor(&ItemCursor { after: 5 }).unwrap();
+
+    assert_eq!(
+        state.decode_cursor::<ItemCursor>(&cursor).unwrap(),
+        ItemCursor { after: 5 }
+    );
+    assert!(state.decode_cursor::<ItemCursor>("not-a-cursor").is_err());
+}
@@ -0,0 +1,64 @@
+use axum::{extract::Request, http::HeaderValue, middleware::Next, response::Response};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// Correlation identifier for a request, accepted from the `x-request-id` header or generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn generate() -> RequestId {
+        RequestId(uuid::Uuid::new_v4().to_string())
+    }
+
+    pub fn from_header(request: &Request) -> Option<RequestId> {
+        request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|header| header.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+            .filter(|id| id.chars().all(|c| c.is_ascii_graphic()))
+            .map(|id| RequestId(id.into()))
+    }
+
+    /// Returns the id of the request being handled by the current task, when the `request_id`
+    /// middleware is attached.
+    pub fn current() -> Option<RequestId> {
+        CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub(crate) fn log_request_id() -> String {
+    RequestId::current()
+        .map(|id| id.0)
+        .unwrap_or_else(|| "-".into())
+}
+
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = RequestId::from_header(&request).unwrap_or_else(RequestId::generate);
+
+    request.extensions_mut().insert(id.clone());
+
+    let mut response = CURRENT_REQUEST_ID
+        .scope(id.clone(), next.run(request))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
@@ -0,0 +1,94 @@
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::middleware::from_fn;
+use axum::{routing::MethodRouter, Extension, Router};
+use http_body_util::BodyExt;
+use hyper::StatusCode;
+use tower::util::ServiceExt;
+
+use crate::api::request_id::{request_id, RequestId, REQUEST_ID_HEADER};
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+use crate::respond_err;
+
+fn app() -> Router {
+    async fn ok_handler(Extension(id): Extension<RequestId>) -> JsonResult<String> {
+        JsonResponse::of(id.0).into()
+    }
+
+    async fn internal_err_handler() -> JsonResult<()> {
+        respond_err!("An unhandled error was propagated!");
+    }
+
+    async fn status_handler() -> JsonResult<JsonStatus> {
+        JsonResponse::of_status(StatusCode::ACCEPTED).into()
+    }
+
+    Router::new()
+        .route("/ok", MethodRouter::new().get(ok_handler))
+        .route("/err", MethodRouter::new().get(internal_err_handler))
+        .route("/status", MethodRouter::new().get(status_handler))
+        .layer(from_fn(request_id))
+}
+
+fn input(uri: &str, id: Option<&str>) -> hyper::Request<axum::body::Body> {
+    let mut request = Request::builder().uri(uri).method(Method::GET);
+    if let Some(id) = id {
+        request = request.header(REQUEST_ID_HEADER, id);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_request_id_is_accepted_and_echoed() {
+    let response = app().oneshot(input("/ok", Some("abc-123"))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: String = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, "abc-123");
+}
+
+#[tokio::test]
+async fn test_request_id_is_generated_when_missing_or_invalid() {
+    for id in [None, Some(""), Some("has spaces")] {
+        let response = app().oneshot(input("/ok", id)).await.unwrap();
+
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(uuid::Uuid::parse_str(&header).is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_request_id_is_included_in_err_body() {
+    let response = app().oneshot(input("/err", Some("abc-123"))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: JsonStatus = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        JsonStatus {
+            reason: Some("Internal Server Error".into()),
+            detail: None,
+            id: Some("abc-123".into()),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_request_id_is_omitted_from_success_status_body() {
+    let response = app()
+        .oneshot(input("/status", Some("abc-123")))
+        .await
+        .unwrap();
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, r#"{"reason":"Accepted","detail":null}"#);
+}
@@ -1,12 +1,20 @@
 use axum::{
     http::HeaderValue,
     response::{IntoResponse, Response},
-    Json,
 };
-use hyper::{header::IntoHeaderName, HeaderMap, StatusCode};
-use log::{error, info};
+use hyper::{
+    header::{IntoHeaderName, CONTENT_TYPE, ETAG},
+    HeaderMap, StatusCode,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::api::cache::vary_on;
+use crate::api::etag::{strong_etag, version_etag, EntityTag, Preconditions};
+use crate::api::exposure::InternalErrorReport;
+use crate::api::negotiate::Encoding;
+use crate::api::request_id::RequestId;
+use crate::telemetry;
+
 pub struct ResponseError(pub anyhow::Error);
 
 pub type JsonResult<T> = core::result::Result<JsonResponse<T>, ResponseError>;
@@ -19,6 +27,10 @@ where
     headers: HeaderMap,
     code: StatusCode,
     body: T,
+    encoding: Encoding,
+    negotiated: bool,
+    etag: Option<EntityTag>,
+    preconditions: Option<Preconditions>,
 }
 
 impl<T> JsonResponse<T>
@@ -33,6 +45,10 @@ where
             headers: HeaderMap::new(),
             code: StatusCode::OK,
             body,
+            encoding: Encoding::Json,
+            negotiated: false,
+            etag: None,
+            preconditions: None,
         }
     }
 
@@ -48,6 +64,34 @@ where
         self
     }
 
+    /// Encodes the body as negotiated from the `accept` header, adding `Accept` to `vary` so
+    /// shared caches don't serve one encoding to clients asking for another.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self.negotiated = true;
+        self
+    }
+
+    /// Sets a strong `etag` computed from the encoded body.
+    pub fn with_etag(mut self) -> Self {
+        self.etag = Some(EntityTag::Computed);
+        self
+    }
+
+    /// Sets a strong `etag` from a caller-provided version, avoiding encoding the body to compute
+    /// it.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.etag = Some(EntityTag::Version(version.into()));
+        self
+    }
+
+    /// Evaluates the request preconditions against the `etag` of a successful response, responding
+    /// with 304 or a 412 status response in place of the body when they fail.
+    pub fn with_preconditions(mut self, preconditions: Preconditions) -> Self {
+        self.preconditions = Some(preconditions);
+        self
+    }
+
     pub fn with_headers(mut self, headers: HeaderMap) -> Self {
         self.headers = headers;
         self
@@ -60,14 +104,88 @@ where
         self.headers.append(key, value);
         self
     }
+
+    pub fn with_header<N>(mut self, key: N, value: HeaderValue) -> Self
+    where
+        N: IntoHeaderName,
+    {
+        self.headers.insert(key, value);
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.code
+    }
+
+    pub fn body(&self) -> &T {
+        &self.body
+    }
+
+    pub fn into_body(self) -> T {
+        self.body
+    }
 }
 
 impl<T> IntoResponse for JsonResponse<T>
 where
     T: Serialize,
 {
-    fn into_response(self) -> Response {
-        let mut response = (self.code, Json(self.body)).into_response();
+    fn into_response(mut self) -> Response {
+        if self.negotiated {
+            vary_on(&mut self.headers, &["Accept"]);
+        }
+
+        // A computed etag hashes the encoded body, which is then reused as the response body.
+        let mut encoded = None;
+        let etag = match &self.etag {
+            Some(EntityTag::Computed) => match self.encoding.encode(&self.body) {
+                Ok(bytes) => {
+                    let etag = strong_etag(&bytes);
+                    encoded = Some(bytes);
+                    Some(etag)
+                }
+                Err(err) => return JsonResponse::of_internal_err(err).into_response(),
+            },
+            Some(EntityTag::Version(version)) => Some(version_etag(version)),
+            None => None,
+        };
+
+        if let Some(etag) = etag {
+            if let Ok(value) = HeaderValue::from_str(&etag) {
+                self.headers.insert(ETAG, value);
+            }
+
+            let failed = match (&self.preconditions, self.code.is_success()) {
+                (Some(preconditions), true) => preconditions.evaluate(&etag),
+                _ => None,
+            };
+
+            match failed {
+                Some(StatusCode::NOT_MODIFIED) => {
+                    let mut response = StatusCode::NOT_MODIFIED.into_response();
+                    for (k, v) in self.headers.iter() {
+                        response.headers_mut().append(k, v.clone());
+                    }
+                    return response;
+                }
+                Some(code) => return JsonResponse::of_status(code).into_response(),
+                None => {}
+            }
+        }
+
+        let bytes = match encoded {
+            Some(bytes) => bytes,
+            None => match self.encoding.encode(&self.body) {
+                Ok(bytes) => bytes,
+                Err(err) => return JsonResponse::of_internal_err(err).into_response(),
+            },
+        };
+        let mut response = (
+            self.code,
+            [(CONTENT_TYPE, self.encoding.content_type())],
+            bytes,
+        )
+            .into_response();
 
         for (k, v) in self.headers.iter() {
             response.headers_mut().append(k, v.clone());
@@ -91,14 +209,17 @@ where
     T: Serialize,
 {
     fn from(response: JsonResponse<T>) -> Self {
-        Ok(response.into())
+        Ok(response)
     }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(schemars::JsonSchema))]
 pub struct JsonStatus {
     pub reason: Option<String>,
     pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
 }
 
 impl JsonStatus {
@@ -106,31 +227,27 @@ impl JsonStatus {
         JsonStatus {
             reason: code.canonical_reason().map(String::from),
             detail,
+            id: match code.is_client_error() || code.is_server_error() {
+                true => RequestId::current().map(|id| id.0),
+                false => None,
+            },
         }
     }
 }
 
 impl JsonResponse<JsonStatus> {
     pub fn of_status(code: StatusCode) -> JsonResponse<JsonStatus> {
-        JsonResponse {
-            headers: HeaderMap::new(),
-            code,
-            body: JsonStatus::of(code, None),
-        }
+        JsonResponse::of(JsonStatus::of(code, None)).with_status(code)
     }
 
     pub fn of_client_err(err: anyhow::Error, code: StatusCode) -> JsonResponse<JsonStatus> {
-        info!("Client error: {} as {}", err, code);
+        telemetry::client_err(&err, code);
 
-        JsonResponse {
-            headers: HeaderMap::new(),
-            code,
-            body: JsonStatus::of(code, None),
-        }
+        JsonResponse::of_status(code)
     }
 
     pub fn of_internal_err(err: anyhow::Error) -> JsonResponse<JsonStatus> {
-        error!("Internal error: {:?}", err);
+        telemetry::internal_err(&err);
 
         JsonResponse::of_status(StatusCode::INTERNAL_SERVER_ERROR)
     }
@@ -158,15 +275,20 @@ where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        return ResponseError(err.into());
+        ResponseError(err.into())
     }
 }
 
 impl IntoResponse for ResponseError {
     fn into_response(self) -> Response {
         match self.0.downcast::<JsonResponse<JsonStatus>>() {
-            Ok(err) => return err.into_response(),
-            Err(unhandled) => JsonResponse::of_internal_err(unhandled).into_response(),
+            Ok(err) => err.into_response(),
+            Err(unhandled) => {
+                let report = InternalErrorReport::of(&unhandled);
+                let mut response = JsonResponse::of_internal_err(unhandled).into_response();
+                response.extensions_mut().insert(report);
+                response
+            }
         }
     }
 }
@@ -48,6 +48,7 @@ async fn assert_status_response(
         JsonStatus {
             reason: expect_code.canonical_reason().map(String::from),
             detail: expect_detail,
+            id: None,
         },
     );
 }
@@ -204,6 +205,7 @@ async fn test_json_to_response() {
         JsonResponse::of(JsonStatus {
             reason: Some("test".into()),
             detail: Some("content".into()),
+            id: None,
         })
         .with_status(StatusCode::IM_A_TEAPOT)
         .into()
@@ -222,6 +224,7 @@ async fn test_json_to_response() {
         JsonStatus {
             reason: Some("test".into()),
             detail: Some("content".into()),
+            id: None,
         },
     )
     .await;
@@ -233,6 +236,7 @@ async fn test_json_to_response_default_ok() {
         JsonResponse::of(JsonStatus {
             reason: Some("test".into()),
             detail: Some("content".into()),
+            id: None,
         })
         .into()
     }
@@ -250,6 +254,7 @@ async fn test_json_to_response_default_ok() {
         JsonStatus {
             reason: Some("test".into()),
             detail: Some("content".into()),
+            id: None,
         },
     )
     .await;
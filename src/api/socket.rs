@@ -0,0 +1,212 @@
+use std::marker::PhantomData;
+
+use axum::{
+    body::Body,
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        FromRequestParts, Query, Request,
+    },
+    http::request::Parts,
+    response::Response,
+};
+use hyper::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::api::response::{JsonResponse, JsonStatus};
+use crate::session::session::{
+    verification_required, AccountStatus, Credential, SessionManager, ACCOUNT_SUSPENDED_DETAIL,
+    SOCKET_TICKET_CLAIMS_TYPE, VERIFICATION_REQUIRED_DETAIL,
+};
+use crate::session::tenant::Tenant;
+use crate::telemetry;
+
+/// Maximum length of a close frame reason, per RFC 6455.
+const MAX_CLOSE_REASON_LEN: usize = 123;
+
+/// Maps a status to a WebSocket close code, using the private `4000 + status` range for statuses
+/// without a standard equivalent.
+pub fn close_code_of(code: StatusCode) -> u16 {
+    match code {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => close_code::INVALID,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => close_code::POLICY,
+        StatusCode::PAYLOAD_TOO_LARGE => close_code::SIZE,
+        StatusCode::SERVICE_UNAVAILABLE | StatusCode::TOO_MANY_REQUESTS => close_code::AGAIN,
+        code if code.is_server_error() => close_code::ERROR,
+        code => 4000 + code.as_u16(),
+    }
+}
+
+fn close_reason_of(status: &JsonStatus) -> String {
+    match serde_json::to_string(status) {
+        Ok(reason) if reason.len() <= MAX_CLOSE_REASON_LEN => reason,
+        _ => status.reason.clone().unwrap_or_default(),
+    }
+}
+
+/// Account of a caller authorized to upgrade to a WebSocket connection, resolved from the request
+/// extensions populated by `resolve`, or from a socket ticket in the `ticket` query parameter.
+/// Tickets without an `iat`, or living longer than `SessionManager::max_socket_ticket_lifetime`
+/// from it, are rejected.
+pub struct SocketCaller<U>(pub U);
+
+#[derive(Deserialize)]
+struct SocketTicket {
+    ticket: String,
+}
+
+impl<U, S> FromRequestParts<S> for SocketCaller<U>
+where
+    U: Clone + Send + Sync + 'static,
+    S: SessionManager<U> + Send + Sync,
+{
+    type Rejection = JsonResponse<JsonStatus>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(account) = parts.extensions.get::<U>() {
+            if verification_required(&parts.extensions) {
+                telemetry::session_event(format_args!(
+                    "Socket upgrade rejected: Account verification was required."
+                ));
+                return Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                    .with_detail(VERIFICATION_REQUIRED_DETAIL));
+            }
+            return Ok(SocketCaller(account.clone()));
+        }
+
+        let unauthorized = || JsonResponse::of_status(StatusCode::UNAUTHORIZED);
+
+        let Ok(Query(SocketTicket { ticket })) = Query::<SocketTicket>::try_from_uri(&parts.uri)
+        else {
+            telemetry::session_event(format_args!(
+                "Socket upgrade rejected: No session or ticket in request."
+            ));
+            return Err(unauthorized());
+        };
+
+        let Ok(claims) = state.decode_claims(Credential(ticket)).await else {
+            telemetry::session_event(format_args!(
+                "Socket upgrade rejected: Unable to decode ticket."
+            ));
+            return Err(unauthorized());
+        };
+
+        if claims.omn_cl_typ != SOCKET_TICKET_CLAIMS_TYPE
+            || state
+                .claims_validation()
+                .validate_at(&claims, state.clock().now_secs())
+                .is_err()
+        {
+            telemetry::session_event(format_args!(
+                "Socket upgrade rejected: Illegal or expired ticket."
+            ));
+            return Err(unauthorized());
+        }
+
+        let lifetime = claims.iat.map(|iat| claims.exp.saturating_sub(iat) as u64);
+        let max_lifetime = state.max_socket_ticket_lifetime().as_secs();
+        if lifetime.is_none_or(|lifetime| lifetime > max_lifetime) {
+            telemetry::session_event(format_args!(
+                "Socket upgrade rejected: Ticket has no issue time or outlives the cap."
+            ));
+            return Err(unauthorized());
+        }
+
+        let request = Request::from_parts(parts.clone(), Body::empty());
+        let tenant = parts
+            .extensions
+            .get::<Tenant>()
+            .cloned()
+            .or_else(|| state.resolve_tenant(&request));
+        let tenant = tenant.as_ref();
+        if !claims.matches_tenant(tenant) {
+            telemetry::session_event(format_args!(
+                "Socket upgrade rejected: Ticket was issued for another tenant."
+            ));
+            return Err(unauthorized());
+        }
+
+        match state.get_tenant_account(tenant, claims.sub).await {
+            Ok(Some((_, AccountStatus::Suspended))) => {
+                Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                    .with_detail(ACCOUNT_SUSPENDED_DETAIL))
+            }
+            Ok(Some((_, AccountStatus::Unverified))) if !state.allows_unverified(&request) => {
+                telemetry::session_event(format_args!(
+                    "Socket upgrade rejected: Account verification was required."
+                ));
+                Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                    .with_detail(VERIFICATION_REQUIRED_DETAIL))
+            }
+            Ok(Some((account, _))) => Ok(SocketCaller(account)),
+            Ok(None) => Err(unauthorized()),
+            Err(err) => Err(JsonResponse::of_internal_err(err)),
+        }
+    }
+}
+
+/// A WebSocket connection framing messages as JSON, receiving `In` and sending `Out`.
+pub struct JsonSocket<In, Out> {
+    socket: WebSocket,
+    _messages: PhantomData<fn(Out) -> In>,
+}
+
+impl<In, Out> JsonSocket<In, Out>
+where
+    In: DeserializeOwned,
+    Out: Serialize,
+{
+    pub fn new(socket: WebSocket) -> JsonSocket<In, Out> {
+        JsonSocket {
+            socket,
+            _messages: PhantomData,
+        }
+    }
+
+    /// Upgrades the connection, handing a `JsonSocket` to the callback.
+    pub fn upgrade<C, Fut>(upgrade: WebSocketUpgrade, callback: C) -> Response
+    where
+        C: FnOnce(JsonSocket<In, Out>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        upgrade.on_upgrade(|socket| callback(JsonSocket::new(socket)))
+    }
+
+    /// Receives the next message, returning `None` once the connection is closed. A frame that
+    /// can't be decoded as `In` is returned as a 400 status, suitable for passing to `close`.
+    pub async fn recv(&mut self) -> Option<Result<In, JsonResponse<JsonStatus>>> {
+        loop {
+            let decoded = match self.socket.recv().await? {
+                Ok(Message::Text(text)) => serde_json::from_str(text.as_str()),
+                Ok(Message::Binary(bytes)) => serde_json::from_slice(&bytes),
+                Ok(Message::Ping(_) | Message::Pong(_)) => continue,
+                Ok(Message::Close(_)) | Err(_) => return None,
+            };
+
+            return Some(decoded.map_err(|err| {
+                JsonResponse::of_client_err(err.into(), StatusCode::BAD_REQUEST)
+                    .with_detail("Malformed message.")
+            }));
+        }
+    }
+
+    pub async fn send(&mut self, message: &Out) -> anyhow::Result<()> {
+        let text = serde_json::to_string(message)?;
+        self.socket.send(Message::Text(text.into())).await?;
+        Ok(())
+    }
+
+    /// Closes the connection with a close code mapped from the status, and the `JsonStatus` body as
+    /// the reason when it fits in a close frame.
+    pub async fn close(mut self, status: JsonResponse<JsonStatus>) -> anyhow::Result<()> {
+        let frame = CloseFrame {
+            code: close_code_of(status.status()),
+            reason: close_reason_of(status.body()).into(),
+        };
+        self.socket.send(Message::Close(Some(frame))).await?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> WebSocket {
+        self.socket
+    }
+}
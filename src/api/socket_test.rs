@@ -0,0 +1,355 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::http::{Method, Request};
+use axum::middleware::from_fn_with_state;
+use axum::response::Response;
+use axum::{routing::get, Router};
+use axum_extra::extract::CookieJar;
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::BodyExt;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+use tower::ServiceExt;
+
+use crate::api::response::{JsonResponse, JsonStatus};
+use crate::api::socket::{close_code_of, JsonSocket, SocketCaller};
+use crate::session::chain::{CredentialChain, CredentialSource, ManagerSource};
+use crate::session::session::{
+    resolve, AccountStatus, Credential, SessionClaims, SessionManager, MAX_SOCKET_TICKET_LIFETIME,
+    VERIFICATION_REQUIRED_DETAIL,
+};
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+struct FakeAppState {}
+
+#[async_trait]
+impl SessionManager<FakeAccount> for Arc<FakeAppState> {
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        Ok(serde_json::from_str(&credential.0)?)
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(Some(FakeAccount { name: account_id }))
+    }
+
+    fn extract_credential(
+        &self,
+        request: &axum::extract::Request,
+        _cookies: &CookieJar,
+    ) -> Option<Credential> {
+        Credential::from_authorization_header(request)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum ClientMessage {
+    Greet,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum ServerMessage {
+    Greeting { text: String },
+}
+
+async fn handler(
+    SocketCaller(caller): SocketCaller<FakeAccount>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    JsonSocket::upgrade(
+        upgrade,
+        move |mut socket: JsonSocket<ClientMessage, ServerMessage>| async move {
+            while let Some(message) = socket.recv().await {
+                match message {
+                    Ok(ClientMessage::Greet) => {
+                        let text = format!("Hello, {}!", caller.name);
+                        socket
+                            .send(&ServerMessage::Greeting { text })
+                            .await
+                            .unwrap();
+                    }
+                    Err(status) => {
+                        socket.close(status).await.unwrap();
+                        return;
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn app() -> Router {
+    let state = Arc::new(FakeAppState {});
+    Router::new()
+        .route("/ws", get(handler))
+        .layer(from_fn_with_state(
+            state.clone(),
+            resolve::<FakeAccount, Arc<FakeAppState>>,
+        ))
+        .with_state(state)
+}
+
+fn ticket(claims: SessionClaims) -> String {
+    let encoded = serde_json::to_string(&claims).unwrap();
+    percent_encode(&encoded)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+async fn serve() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app()).await.unwrap() });
+    format!("ws://{}/ws", addr)
+}
+
+#[test]
+fn test_close_code_of_status() {
+    assert_eq!(close_code_of(StatusCode::BAD_REQUEST), 1007);
+    assert_eq!(close_code_of(StatusCode::FORBIDDEN), 1008);
+    assert_eq!(close_code_of(StatusCode::INTERNAL_SERVER_ERROR), 1011);
+    assert_eq!(close_code_of(StatusCode::SERVICE_UNAVAILABLE), 1013);
+    assert_eq!(close_code_of(StatusCode::NOT_FOUND), 4404);
+}
+
+#[tokio::test]
+async fn test_upgrade_without_session_or_ticket_is_rejected() {
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri("/ws")
+                .method(Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: JsonStatus = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, JsonStatus::of(StatusCode::UNAUTHORIZED, None));
+}
+
+#[tokio::test]
+async fn test_upgrade_with_session_claims_as_ticket_is_rejected() {
+    let claims = SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap();
+
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ws?ticket={}", ticket(claims)))
+                .method(Method::GET)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_socket_ticket_lifetime_is_capped() {
+    let claims =
+        SessionClaims::new_socket_ticket("Test Account", Duration::from_secs(3600)).unwrap();
+
+    assert_eq!(
+        (claims.exp - claims.iat.unwrap()) as u64,
+        MAX_SOCKET_TICKET_LIFETIME.as_secs()
+    );
+}
+
+#[tokio::test]
+async fn test_long_lived_or_undated_ticket_is_rejected() {
+    let valid = SessionClaims::new_socket_ticket("Test Account", Duration::from_secs(30)).unwrap();
+    let long_lived = SessionClaims {
+        exp: valid.exp + 3600,
+        ..valid.clone()
+    };
+    let undated = SessionClaims { iat: None, ..valid };
+
+    for claims in [long_lived, undated] {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/ws?ticket={}", ticket(claims)))
+                    .method(Method::GET)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn test_ticket_upgrade_exchanges_typed_messages() {
+    let url = serve().await;
+    let claims = SessionClaims::new_socket_ticket("Test Account", Duration::from_secs(30)).unwrap();
+
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("{}?ticket={}", url, ticket(claims)))
+            .await
+            .unwrap();
+
+    socket
+        .send(Message::text(r#"{"type":"Greet"}"#))
+        .await
+        .unwrap();
+
+    let reply = socket.next().await.unwrap().unwrap();
+    let reply: ServerMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert_eq!(
+        reply,
+        ServerMessage::Greeting {
+            text: "Hello, Test Account!".into()
+        }
+    );
+
+    socket.send(Message::text("not json")).await.unwrap();
+
+    match socket.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => {
+            assert_eq!(frame.code, CloseCode::Invalid);
+            let reason: JsonStatus = serde_json::from_str(frame.reason.as_str()).unwrap();
+            assert_eq!(
+                reason,
+                JsonResponse::of_status(StatusCode::BAD_REQUEST)
+                    .with_detail("Malformed message.")
+                    .into_body()
+            );
+        }
+        message => panic!("Expected close frame, got {:?}", message),
+    }
+}
+
+#[tokio::test]
+async fn test_ticket_upgrade_of_unverified_account_is_forbidden_unless_allowed() {
+    let sessions = FakeSessionManager::new().with_unverified_route("/ws/unverified");
+    sessions.insert_account_with_status(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+        AccountStatus::Unverified,
+    );
+    let app = Router::new()
+        .route(
+            "/ws",
+            get(|SocketCaller(caller): SocketCaller<FakeAccount>| async move { caller.name }),
+        )
+        .route(
+            "/ws/unverified",
+            get(|SocketCaller(caller): SocketCaller<FakeAccount>| async move { caller.name }),
+        )
+        .with_state(sessions);
+    let claims =
+        SessionClaims::new_socket_ticket("test-account-id", Duration::from_secs(30)).unwrap();
+    let ticket = percent_encode(&FakeSessionManager::<FakeAccount>::encode_claims(&claims).0);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ws?ticket={}", ticket))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: JsonStatus = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body.detail.as_deref(), Some(VERIFICATION_REQUIRED_DETAIL));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ws/unverified?ticket={}", ticket))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+struct ApiKeySource;
+
+#[async_trait]
+impl CredentialSource for ApiKeySource {
+    fn name(&self) -> &str {
+        "api_key"
+    }
+
+    fn extract_credential(
+        &self,
+        _request: &axum::extract::Request,
+        _cookies: &CookieJar,
+    ) -> Option<Credential> {
+        None
+    }
+
+    async fn decode_claims(&self, _credential: Credential) -> anyhow::Result<SessionClaims> {
+        anyhow::bail!("Unknown API key")
+    }
+}
+
+#[tokio::test]
+async fn test_ticket_upgrade_through_credential_chain() {
+    let sessions = FakeSessionManager::new().with_account(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+    );
+    let chain = CredentialChain::new(sessions.clone())
+        .with_source(ApiKeySource)
+        .with_source(ManagerSource::new("bearer", sessions));
+    let app = Router::new()
+        .route(
+            "/ws",
+            get(|SocketCaller(caller): SocketCaller<FakeAccount>| async move { caller.name }),
+        )
+        .with_state(chain);
+    let claims =
+        SessionClaims::new_socket_ticket("test-account-id", Duration::from_secs(30)).unwrap();
+    let ticket = percent_encode(&FakeSessionManager::<FakeAccount>::encode_claims(&claims).0);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/ws?ticket={}", ticket))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "Test Account");
+}
@@ -0,0 +1,282 @@
+use std::{collections::VecDeque, convert::Infallible, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderValue},
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
+};
+use futures_util::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use hyper::{header::IntoHeaderName, HeaderMap};
+use serde::Serialize;
+
+use crate::api::request_id::RequestId;
+use crate::api::stream::StreamErrorRecord;
+
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// Event name used for the trailing event written when the stream yields an error.
+pub const SSE_ERROR_EVENT: &str = "error";
+
+/// A typed server-sent event, with data serialized as JSON.
+#[derive(Clone, Debug)]
+pub struct SseEvent<T> {
+    pub data: T,
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub retry: Option<Duration>,
+}
+
+impl<T> SseEvent<T>
+where
+    T: Serialize,
+{
+    pub fn of(data: T) -> SseEvent<T> {
+        SseEvent {
+            data,
+            event: None,
+            id: None,
+            retry: None,
+        }
+    }
+
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    fn into_event(self) -> anyhow::Result<Event> {
+        let mut event = Event::default().json_data(&self.data)?;
+
+        if let Some(name) = self.event {
+            event = event.event(checked_field("event", name)?);
+        }
+        if let Some(id) = self.id {
+            event = event.id(checked_field("id", id)?);
+        }
+        if let Some(retry) = self.retry {
+            event = event.retry(retry);
+        }
+
+        Ok(event)
+    }
+}
+
+/// Rejects a field value that would break the event framing, which axum panics on.
+fn checked_field(field: &str, value: String) -> anyhow::Result<String> {
+    if value.contains(['\n', '\r', '\0']) {
+        anyhow::bail!(
+            "Illegal line break or NUL in the event {}: {:?}",
+            field,
+            value
+        );
+    }
+    Ok(value)
+}
+
+/// Source of past events for resuming a stream from the `last-event-id` sent by a reconnecting
+/// client.
+#[async_trait]
+pub trait ReplayBuffer<T> {
+    /// Returns the buffered events following the event with the given id, oldest first.
+    async fn replay_after(&self, last_event_id: &str) -> anyhow::Result<Vec<SseEvent<T>>>;
+}
+
+/// Bounded in-memory `ReplayBuffer`, evicting the oldest events beyond its capacity. Replays every
+/// buffered event when the last event id is no longer buffered.
+pub struct MemoryReplayBuffer<T> {
+    capacity: usize,
+    events: Mutex<VecDeque<SseEvent<T>>>,
+}
+
+impl<T> MemoryReplayBuffer<T> {
+    pub fn new(capacity: usize) -> MemoryReplayBuffer<T> {
+        MemoryReplayBuffer {
+            capacity,
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, event: SseEvent<T>) {
+        let mut events = self.events.lock().unwrap_or_else(|err| err.into_inner());
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+#[async_trait]
+impl<T> ReplayBuffer<T> for MemoryReplayBuffer<T>
+where
+    T: Clone + Send + Sync,
+{
+    async fn replay_after(&self, last_event_id: &str) -> anyhow::Result<Vec<SseEvent<T>>> {
+        let events = self.events.lock().unwrap_or_else(|err| err.into_inner());
+
+        let start = events
+            .iter()
+            .position(|event| event.id.as_deref() == Some(last_event_id))
+            .map_or(0, |position| position + 1);
+
+        Ok(events.iter().skip(start).cloned().collect())
+    }
+}
+
+/// Extracts the `last-event-id` header sent by a reconnecting client.
+#[derive(Clone, Debug, Default)]
+pub struct LastEventId(pub Option<String>);
+
+impl<S> FromRequestParts<S> for LastEventId
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(LastEventId(
+            parts
+                .headers
+                .get(LAST_EVENT_ID_HEADER)
+                .and_then(|header| header.to_str().ok())
+                .map(String::from),
+        ))
+    }
+}
+
+/// Server-sent events counterpart to `JsonResponse`, writing typed events from a stream.
+pub struct SseResponse<T> {
+    headers: HeaderMap,
+    keep_alive: Option<KeepAlive>,
+    stream: BoxStream<'static, anyhow::Result<SseEvent<T>>>,
+}
+
+impl<T> SseResponse<T>
+where
+    T: Serialize + Send + 'static,
+{
+    pub fn of<S>(stream: S) -> SseResponse<T>
+    where
+        S: Stream<Item = anyhow::Result<SseEvent<T>>> + Send + 'static,
+    {
+        SseResponse {
+            headers: HeaderMap::new(),
+            keep_alive: None,
+            stream: stream.boxed(),
+        }
+    }
+
+    pub fn of_items<S>(stream: S) -> SseResponse<T>
+    where
+        S: Stream<Item = anyhow::Result<T>> + Send + 'static,
+    {
+        SseResponse::of(stream.map(|item| item.map(SseEvent::of)))
+    }
+
+    /// Writes the events buffered since the client's last event id, if any, before the live stream.
+    pub async fn resume<S, R>(
+        replay: &R,
+        last_event_id: &LastEventId,
+        stream: S,
+    ) -> anyhow::Result<SseResponse<T>>
+    where
+        S: Stream<Item = anyhow::Result<SseEvent<T>>> + Send + 'static,
+        R: ReplayBuffer<T> + Sync,
+    {
+        let replayed = match &last_event_id.0 {
+            Some(id) => replay.replay_after(id).await?,
+            None => Vec::new(),
+        };
+
+        Ok(SseResponse::of(
+            stream::iter(replayed.into_iter().map(Ok)).chain(stream),
+        ))
+    }
+
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(KeepAlive::new().interval(interval));
+        self
+    }
+
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn append_header<N>(mut self, key: N, value: HeaderValue) -> Self
+    where
+        N: IntoHeaderName,
+    {
+        self.headers.append(key, value);
+        self
+    }
+}
+
+fn encode_events<T>(
+    stream: BoxStream<'static, anyhow::Result<SseEvent<T>>>,
+    request_id: Option<RequestId>,
+) -> impl Stream<Item = Result<Event, Infallible>> + Send + 'static
+where
+    T: Serialize + Send + 'static,
+{
+    stream::unfold(Some(stream), move |stream| {
+        let request_id = request_id.clone();
+        async move {
+            let mut stream = stream?;
+
+            let event = match stream.next().await? {
+                Ok(event) => event.into_event(),
+                Err(err) => Err(err),
+            };
+
+            match event {
+                Ok(event) => Some((event, Some(stream))),
+                Err(err) => {
+                    let record = StreamErrorRecord::of(err, request_id.as_ref());
+                    let event = Event::default()
+                        .event(SSE_ERROR_EVENT)
+                        .json_data(&record.error)
+                        .unwrap_or_default();
+                    Some((event, None))
+                }
+            }
+        }
+    })
+    .map(Ok)
+}
+
+impl<T> IntoResponse for SseResponse<T>
+where
+    T: Serialize + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        let events = encode_events(self.stream, RequestId::current());
+
+        let mut response = match self.keep_alive {
+            Some(keep_alive) => Sse::new(events).keep_alive(keep_alive).into_response(),
+            None => Sse::new(events).into_response(),
+        };
+
+        for (k, v) in self.headers.iter() {
+            response.headers_mut().append(k, v.clone());
+        }
+
+        response
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::middleware::from_fn_with_state;
+use axum::{routing::MethodRouter, Extension, Router};
+use futures_util::stream;
+use http_body_util::BodyExt;
+use hyper::header::CONTENT_TYPE;
+use hyper::StatusCode;
+use serde::Serialize;
+use tower::util::ServiceExt;
+
+use crate::api::response::JsonResponse;
+use crate::api::sse::{
+    LastEventId, MemoryReplayBuffer, SseEvent, SseResponse, LAST_EVENT_ID_HEADER,
+};
+use crate::session::session::{authorize, resolve};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone, Debug, Serialize)]
+struct Update {
+    n: u32,
+}
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+fn input(uri: &str, last_event_id: Option<&str>) -> hyper::Request<axum::body::Body> {
+    let mut request = Request::builder().uri(uri).method(Method::GET);
+    if let Some(id) = last_event_id {
+        request = request.header(LAST_EVENT_ID_HEADER, id);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+fn replay_buffer() -> Arc<MemoryReplayBuffer<Update>> {
+    let buffer = MemoryReplayBuffer::new(2);
+    for n in 1..=3 {
+        buffer.push(SseEvent::of(Update { n }).with_id(n.to_string()));
+    }
+    Arc::new(buffer)
+}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/events",
+            MethodRouter::new().get(|| async {
+                SseResponse::of(stream::iter(vec![Ok(SseEvent::of(Update { n: 1 })
+                    .with_event("update")
+                    .with_id("1")
+                    .with_retry(Duration::from_secs(5)))]))
+                .with_keep_alive(Duration::from_secs(15))
+            }),
+        )
+        .route(
+            "/items-err",
+            MethodRouter::new().get(|| async {
+                SseResponse::of_items(stream::iter(vec![
+                    Ok(Update { n: 1 }),
+                    Err(JsonResponse::of_status(StatusCode::GONE).anyhow()),
+                    Ok(Update { n: 3 }),
+                ]))
+            }),
+        )
+        .route(
+            "/resume",
+            MethodRouter::new().get(
+                |Extension(replay): Extension<Arc<MemoryReplayBuffer<Update>>>,
+                 last_event_id: LastEventId| async move {
+                    let live = SseEvent::of(Update { n: 4 }).with_id("4");
+                    SseResponse::resume(
+                        replay.as_ref(),
+                        &last_event_id,
+                        stream::iter(vec![Ok(live)]),
+                    )
+                    .await
+                    .unwrap()
+                },
+            ),
+        )
+        .route(
+            "/illegal-id",
+            MethodRouter::new().get(|| async {
+                SseResponse::of(stream::iter(vec![
+                    Ok(SseEvent::of(Update { n: 1 }).with_id("1")),
+                    Ok(SseEvent::of(Update { n: 2 }).with_id("2\ndata: forged")),
+                ]))
+            }),
+        )
+        .layer(Extension(replay_buffer()))
+}
+
+fn account_app(sessions: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/account",
+                MethodRouter::new().get(|Extension(caller): Extension<FakeAccount>| async move {
+                    SseResponse::of_items(stream::iter(vec![Ok(format!(
+                        "Hello, {}!",
+                        caller.name
+                    ))]))
+                }),
+            )
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(sessions),
+    )
+}
+
+async fn body_string(response: axum::response::Response<Body>) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_typed_event_to_response() {
+    let response = app().oneshot(input("/events", None)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+    assert_eq!(
+        body_string(response).await,
+        "data: {\"n\":1}\nevent: update\nid: 1\nretry: 5000\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_err_writes_error_event() {
+    let response = app().oneshot(input("/items-err", None)).await.unwrap();
+
+    assert_eq!(
+        body_string(response).await,
+        "data: {\"n\":1}\n\nevent: error\ndata: {\"reason\":\"Gone\",\"detail\":null}\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_resume_replays_after_last_event_id() {
+    let response = app().oneshot(input("/resume", Some("2"))).await.unwrap();
+
+    assert_eq!(
+        body_string(response).await,
+        "data: {\"n\":3}\nid: 3\n\ndata: {\"n\":4}\nid: 4\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_resume_replays_all_buffered_when_last_event_id_evicted() {
+    let response = app().oneshot(input("/resume", Some("1"))).await.unwrap();
+
+    assert_eq!(
+        body_string(response).await,
+        "data: {\"n\":2}\nid: 2\n\ndata: {\"n\":3}\nid: 3\n\ndata: {\"n\":4}\nid: 4\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_resume_without_last_event_id_is_live_only() {
+    let response = app().oneshot(input("/resume", None)).await.unwrap();
+
+    assert_eq!(body_string(response).await, "data: {\"n\":4}\nid: 4\n\n");
+}
+
+#[tokio::test]
+async fn test_illegal_event_id_writes_error_event() {
+    let response = app().oneshot(input("/illegal-id", None)).await.unwrap();
+
+    assert_eq!(
+        body_string(response).await,
+        "data: {\"n\":1}\nid: 1\n\nevent: error\ndata: {\"reason\":\"Internal Server Error\",\"detail\":null}\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_resolved_account_is_available_to_stream() {
+    let sessions = FakeSessionManager::new().with_account(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+    );
+    let session = sessions.mint_session("test-account-id");
+    let client = account_app(sessions);
+
+    let response = client.get("/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "data: \"Hello, Test Account!\"\n\n");
+
+    client
+        .get("/account")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
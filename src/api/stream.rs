@@ -0,0 +1,230 @@
+use std::pin::Pin;
+
+use axum::{
+    body::{Body, Bytes},
+    http::HeaderValue,
+    response::{IntoResponse, Response},
+};
+use futures_util::{stream, Stream, StreamExt};
+use hyper::{
+    header::{IntoHeaderName, CONTENT_TYPE},
+    HeaderMap, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::request_id::RequestId;
+use crate::api::response::{JsonResponse, JsonStatus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// One JSON value per line, as `application/x-ndjson`.
+    Ndjson,
+    /// A single incrementally-encoded JSON array, as `application/json`. An error aborts the body,
+    /// leaving the array unterminated.
+    JsonArray,
+}
+
+impl StreamFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            StreamFormat::Ndjson => "application/x-ndjson",
+            StreamFormat::JsonArray => "application/json",
+        }
+    }
+
+    fn open(&self) -> Option<Bytes> {
+        match self {
+            StreamFormat::Ndjson => None,
+            StreamFormat::JsonArray => Some(Bytes::from_static(b"[")),
+        }
+    }
+
+    fn frame(&self, mut encoded: Vec<u8>, first: bool) -> Bytes {
+        match self {
+            StreamFormat::Ndjson => encoded.push(b'\n'),
+            StreamFormat::JsonArray if !first => encoded.insert(0, b','),
+            StreamFormat::JsonArray => {}
+        }
+        Bytes::from(encoded)
+    }
+
+    fn close(&self) -> Option<Bytes> {
+        match self {
+            StreamFormat::Ndjson => None,
+            StreamFormat::JsonArray => Some(Bytes::from_static(b"]")),
+        }
+    }
+}
+
+/// Trailing record written in place of the next item when an `Ndjson` stream yields an error. No
+/// further items are written after an error record.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct StreamErrorRecord {
+    pub error: JsonStatus,
+}
+
+impl StreamErrorRecord {
+    pub(crate) fn of(err: anyhow::Error, request_id: Option<&RequestId>) -> StreamErrorRecord {
+        let mut error = match err.downcast::<JsonResponse<JsonStatus>>() {
+            Ok(status) => status.into_body(),
+            Err(unhandled) => JsonResponse::of_internal_err(unhandled).into_body(),
+        };
+
+        if error.id.is_none() {
+            error.id = request_id.map(|id| id.0.clone());
+        }
+
+        StreamErrorRecord { error }
+    }
+}
+
+/// Aborts the body of a `JsonArray` stream, as no array element can carry the error without being
+/// mistaken for an item.
+#[derive(Debug)]
+struct StreamAborted(JsonStatus);
+
+impl std::fmt::Display for StreamAborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Stream aborted: {}",
+            self.0.reason.as_deref().unwrap_or("Unknown error")
+        )
+    }
+}
+
+impl std::error::Error for StreamAborted {}
+
+/// Streaming counterpart to `JsonResponse`, writing serializable items from a stream as they are
+/// produced instead of buffering the whole body.
+pub struct StreamResponse<S> {
+    headers: HeaderMap,
+    code: StatusCode,
+    format: StreamFormat,
+    stream: S,
+}
+
+impl<S, T> StreamResponse<S>
+where
+    S: Stream<Item = anyhow::Result<T>> + Send + 'static,
+    T: Serialize,
+{
+    pub fn of(stream: S, format: StreamFormat) -> StreamResponse<S> {
+        StreamResponse {
+            headers: HeaderMap::new(),
+            code: StatusCode::OK,
+            format,
+            stream,
+        }
+    }
+
+    pub fn ndjson(stream: S) -> StreamResponse<S> {
+        StreamResponse::of(stream, StreamFormat::Ndjson)
+    }
+
+    pub fn json_array(stream: S) -> StreamResponse<S> {
+        StreamResponse::of(stream, StreamFormat::JsonArray)
+    }
+
+    pub fn with_status(mut self, code: StatusCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn append_header<N>(mut self, key: N, value: HeaderValue) -> Self
+    where
+        N: IntoHeaderName,
+    {
+        self.headers.append(key, value);
+        self
+    }
+}
+
+struct EncodeState<S> {
+    stream: Pin<Box<S>>,
+    format: StreamFormat,
+    request_id: Option<RequestId>,
+    first: bool,
+    done: bool,
+}
+
+fn encode_items<S, T>(
+    stream: S,
+    format: StreamFormat,
+    request_id: Option<RequestId>,
+) -> impl Stream<Item = Result<Bytes, StreamAborted>> + Send + 'static
+where
+    S: Stream<Item = anyhow::Result<T>> + Send + 'static,
+    T: Serialize,
+{
+    let state = EncodeState {
+        stream: Box::pin(stream),
+        format,
+        request_id,
+        first: true,
+        done: false,
+    };
+
+    let items = stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+
+        let encoded = match state.stream.next().await {
+            Some(Ok(item)) => serde_json::to_vec(&item).map_err(anyhow::Error::from),
+            Some(Err(err)) => Err(err),
+            None => {
+                state.done = true;
+                return state.format.close().map(|close| (Ok(close), state));
+            }
+        };
+
+        let chunk = match encoded {
+            Ok(encoded) => Ok(state.format.frame(encoded, state.first)),
+            Err(err) => {
+                state.done = true;
+                let record = StreamErrorRecord::of(err, state.request_id.as_ref());
+                match state.format {
+                    StreamFormat::Ndjson => {
+                        let encoded = serde_json::to_vec(&record).unwrap_or_default();
+                        Ok(state.format.frame(encoded, state.first))
+                    }
+                    StreamFormat::JsonArray => Err(StreamAborted(record.error)),
+                }
+            }
+        };
+
+        state.first = false;
+        Some((chunk, state))
+    });
+
+    stream::iter(format.open().map(Ok)).chain(items)
+}
+
+impl<S, T> IntoResponse for StreamResponse<S>
+where
+    S: Stream<Item = anyhow::Result<T>> + Send + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let body = Body::from_stream(encode_items(self.stream, self.format, RequestId::current()));
+
+        let mut response = (
+            self.code,
+            [(CONTENT_TYPE, self.format.content_type())],
+            body,
+        )
+            .into_response();
+
+        for (k, v) in self.headers.iter() {
+            response.headers_mut().append(k, v.clone());
+        }
+
+        response
+    }
+}
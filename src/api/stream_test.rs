@@ -0,0 +1,128 @@
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::{routing::MethodRouter, Router};
+use futures_util::stream;
+use http_body_util::BodyExt;
+use hyper::header::CONTENT_TYPE;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use tower::util::ServiceExt;
+
+use crate::api::response::{JsonResponse, JsonStatus};
+use crate::api::stream::{StreamErrorRecord, StreamResponse};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    n: u32,
+}
+
+fn items(fail_at: Option<u32>) -> Vec<anyhow::Result<Item>> {
+    (1..=3)
+        .map(|n| match fail_at {
+            Some(fail_at) if n == fail_at => Err(anyhow::anyhow!("Database went away!")),
+            _ => Ok(Item { n }),
+        })
+        .collect()
+}
+
+fn input(uri: &str) -> hyper::Request<axum::body::Body> {
+    Request::builder()
+        .uri(uri)
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/ndjson",
+            MethodRouter::new().get(|| async { StreamResponse::ndjson(stream::iter(items(None))) }),
+        )
+        .route(
+            "/array",
+            MethodRouter::new().get(|| async {
+                StreamResponse::json_array(stream::iter(items(None)))
+                    .with_status(StatusCode::PARTIAL_CONTENT)
+            }),
+        )
+        .route(
+            "/array-err",
+            MethodRouter::new()
+                .get(|| async { StreamResponse::json_array(stream::iter(items(Some(2)))) }),
+        )
+        .route(
+            "/ndjson-status-err",
+            MethodRouter::new().get(|| async {
+                StreamResponse::ndjson(stream::iter(vec![
+                    Ok(Item { n: 1 }),
+                    Err(JsonResponse::of_status(StatusCode::CONFLICT)
+                        .with_detail("Changed underneath.")
+                        .anyhow()),
+                    Ok(Item { n: 3 }),
+                ]))
+            }),
+        )
+}
+
+async fn body_string(response: axum::response::Response<Body>) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_ndjson_stream_to_response() {
+    let response = app().oneshot(input("/ndjson")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/x-ndjson");
+    assert_eq!(
+        body_string(response).await,
+        "{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n"
+    );
+}
+
+#[tokio::test]
+async fn test_json_array_stream_to_response() {
+    let response = app().oneshot(input("/array")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+    let body: Vec<Item> = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body, vec![Item { n: 1 }, Item { n: 2 }, Item { n: 3 }]);
+}
+
+#[tokio::test]
+async fn test_json_array_stream_err_aborts_body() {
+    let response = app().oneshot(input("/array-err")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut body = response.into_body();
+    let mut written = Vec::new();
+    let err = loop {
+        match body.frame().await.unwrap() {
+            Ok(frame) => written.extend_from_slice(&frame.into_data().unwrap()),
+            Err(err) => break err,
+        }
+    };
+
+    assert_eq!(String::from_utf8(written).unwrap(), r#"[{"n":1}"#);
+    assert!(err.to_string().contains("Internal Server Error"));
+}
+
+#[tokio::test]
+async fn test_ndjson_stream_status_err_writes_trailing_record() {
+    let response = app().oneshot(input("/ndjson-status-err")).await.unwrap();
+
+    let body = body_string(response).await;
+    let lines: Vec<&str> = body.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        serde_json::from_str::<StreamErrorRecord>(lines[1]).unwrap(),
+        StreamErrorRecord {
+            error: JsonStatus::of(StatusCode::CONFLICT, Some("Changed underneath.".into())),
+        }
+    );
+}
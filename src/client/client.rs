@@ -0,0 +1,205 @@
+use axum::body::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    header::{HeaderName, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    HeaderMap, Method, Request, StatusCode,
+};
+use hyper_util::{
+    client::legacy::{
+        connect::{Connect, HttpConnector},
+        Client,
+    },
+    rt::TokioExecutor,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::api::response::JsonStatus;
+use crate::session::session::Credential;
+
+/// Error from a call to an omnium-style API, the inverse of a `ResponseError`.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The API responded with an error status, and a `JsonStatus` body when one could be decoded.
+    Status {
+        code: StatusCode,
+        status: JsonStatus,
+    },
+    /// The request could not be sent, or the response could not be read or decoded.
+    Transport(anyhow::Error),
+}
+
+impl ClientError {
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Status { code, .. } => Some(*code),
+            ClientError::Transport(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Status { code, status } => match &status.detail {
+                Some(detail) => write!(f, "{}: {}", code, detail),
+                None => write!(f, "{}", code),
+            },
+            ClientError::Transport(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+fn transport<E: Into<anyhow::Error>>(err: E) -> ClientError {
+    ClientError::Transport(err.into())
+}
+
+/// Client for calling omnium-style APIs, decoding success bodies into a type and error bodies
+/// into a `ClientError`.
+#[derive(Clone)]
+pub struct ApiClient<C = HttpConnector> {
+    client: Client<C, Full<Bytes>>,
+    base_uri: String,
+    credential: Option<Credential>,
+}
+
+impl ApiClient<HttpConnector> {
+    pub fn new(base_uri: &str) -> ApiClient<HttpConnector> {
+        ApiClient::from_client(Client::builder(TokioExecutor::new()).build_http(), base_uri)
+    }
+}
+
+impl<C> ApiClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    /// Creates a client over a configured hyper client, such as one with a TLS connector.
+    pub fn from_client(client: Client<C, Full<Bytes>>, base_uri: &str) -> ApiClient<C> {
+        ApiClient {
+            client,
+            base_uri: base_uri.trim_end_matches('/').into(),
+            credential: None,
+        }
+    }
+
+    /// Sends the credential as a `Bearer` authorization header on every request.
+    pub fn with_credential(mut self, credential: Credential) -> Self {
+        self.credential = Some(credential);
+        self
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> ApiRequest<'_, C> {
+        ApiRequest {
+            client: self,
+            method,
+            path: path.into(),
+            headers: HeaderMap::new(),
+            credential: self.credential.clone(),
+            body: None,
+        }
+    }
+
+    pub fn get(&self, path: &str) -> ApiRequest<'_, C> {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> ApiRequest<'_, C> {
+        self.request(Method::POST, path)
+    }
+
+    pub fn put(&self, path: &str) -> ApiRequest<'_, C> {
+        self.request(Method::PUT, path)
+    }
+
+    pub fn patch(&self, path: &str) -> ApiRequest<'_, C> {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> ApiRequest<'_, C> {
+        self.request(Method::DELETE, path)
+    }
+}
+
+pub struct ApiRequest<'a, C> {
+    client: &'a ApiClient<C>,
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    credential: Option<Credential>,
+    body: Option<anyhow::Result<Vec<u8>>>,
+}
+
+impl<C> ApiRequest<'_, C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    pub fn json<B: Serialize>(mut self, body: &B) -> Self {
+        self.body = Some(serde_json::to_vec(body).map_err(anyhow::Error::from));
+        self
+    }
+
+    pub fn header(mut self, key: HeaderName, value: hyper::header::HeaderValue) -> Self {
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Overrides the client's credential for this request.
+    pub fn credential(mut self, credential: Credential) -> Self {
+        self.credential = Some(credential);
+        self
+    }
+
+    pub async fn send<T: DeserializeOwned>(self) -> Result<T, ClientError> {
+        let mut request = Request::builder()
+            .method(self.method)
+            .uri(format!("{}{}", self.client.base_uri, self.path))
+            .header(ACCEPT, "application/json");
+
+        if let Some(Credential(token)) = &self.credential {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let body = match self.body.transpose().map_err(transport)? {
+            Some(body) => {
+                request = request.header(CONTENT_TYPE, "application/json");
+                Full::new(Bytes::from(body))
+            }
+            None => Full::new(Bytes::new()),
+        };
+
+        let mut request = request.body(body).map_err(transport)?;
+        request.headers_mut().extend(self.headers);
+
+        let response = self
+            .client
+            .client
+            .request(request)
+            .await
+            .map_err(transport)?;
+        let code = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(transport)?
+            .to_bytes();
+
+        if code.is_success() {
+            let body: &[u8] = match body.is_empty() {
+                true => b"null",
+                false => &body,
+            };
+            return serde_json::from_slice(body).map_err(transport);
+        }
+
+        // Not `JsonStatus::of`, which would take the id of the request being handled locally.
+        let status = serde_json::from_slice(&body).unwrap_or_else(|_| JsonStatus {
+            reason: code.canonical_reason().map(String::from),
+            detail: None,
+            id: None,
+        });
+
+        Err(ClientError::Status { code, status })
+    }
+}
@@ -0,0 +1,145 @@
+use axum::extract::Json;
+use axum::http::HeaderMap;
+use axum::middleware::from_fn;
+use axum::{routing::get, Router};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::api::request_id::request_id;
+use crate::api::response::{JsonResponse, JsonResult, JsonStatus};
+use crate::client::client::{ApiClient, ClientError};
+use crate::respond_err;
+use crate::session::session::Credential;
+use crate::testing::client::TestClient;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    name: String,
+}
+
+fn app() -> Router {
+    async fn whoami(headers: HeaderMap) -> JsonResult<String> {
+        let Some(header) = headers.get("authorization") else {
+            respond_err!(
+                JsonResponse::of_status(StatusCode::UNAUTHORIZED).with_detail("No credential.")
+            );
+        };
+        JsonResponse::of(header.to_str()?.to_string()).into()
+    }
+
+    async fn create(Json(item): Json<Item>) -> JsonResult<Item> {
+        JsonResponse::of(item)
+            .with_status(StatusCode::CREATED)
+            .into()
+    }
+
+    async fn conflict() -> JsonResult<Item> {
+        respond_err!(JsonResponse::of_status(StatusCode::CONFLICT).with_detail("Already exists."));
+    }
+
+    async fn accepted() -> StatusCode {
+        StatusCode::ACCEPTED
+    }
+
+    Router::new()
+        .route("/whoami", get(whoami))
+        .route("/items", get(conflict).post(create))
+        .route("/accepted", get(accepted))
+}
+
+async fn serve() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app()).await.unwrap() });
+    format!("http://{}/", addr)
+}
+
+#[tokio::test]
+async fn test_credential_is_sent_as_bearer() {
+    let client = ApiClient::new(&serve().await).with_credential(Credential("token".into()));
+
+    let caller: String = client.get("/whoami").send().await.unwrap();
+
+    assert_eq!(caller, "Bearer token");
+}
+
+#[tokio::test]
+async fn test_json_body_is_sent_and_decoded() {
+    let client = ApiClient::new(&serve().await);
+
+    let item: Item = client
+        .post("/items")
+        .json(&Item {
+            name: "test".into(),
+        })
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        item,
+        Item {
+            name: "test".into()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_empty_success_body_is_decoded() {
+    let client = ApiClient::new(&serve().await);
+
+    client.get("/accepted").send::<()>().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_err_status_is_decoded() {
+    let client = ApiClient::new(&serve().await);
+
+    let err = client.get("/items").send::<Item>().await.unwrap_err();
+
+    assert_eq!(err.status_code(), Some(StatusCode::CONFLICT));
+    match err {
+        ClientError::Status { code, status } => {
+            assert_eq!(code, StatusCode::CONFLICT);
+            assert_eq!(
+                status,
+                JsonStatus::of(StatusCode::CONFLICT, Some("Already exists.".into()))
+            );
+        }
+        err => panic!("Expected status error, got {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_missing_route_is_status_without_body() {
+    let client = ApiClient::new(&serve().await);
+
+    let err = client.get("/missing").send::<Item>().await.unwrap_err();
+
+    assert_eq!(err.to_string(), "404 Not Found");
+}
+
+#[tokio::test]
+async fn test_missing_route_status_has_no_local_request_id() {
+    let base = serve().await;
+    let proxy = Router::new()
+        .route(
+            "/proxy",
+            get(move || async move {
+                let err = ApiClient::new(&base)
+                    .get("/missing")
+                    .send::<Item>()
+                    .await
+                    .unwrap_err();
+                match err {
+                    ClientError::Status { status, .. } => format!("{:?}", status.id),
+                    err => panic!("Expected status error, got {:?}", err),
+                }
+            }),
+        )
+        .layer(from_fn(request_id));
+
+    let response = TestClient::new(proxy).get("/proxy").send().await;
+
+    assert_eq!(response.text(), "None");
+}
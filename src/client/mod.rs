@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod client;
+#[cfg(test)]
+mod client_test;
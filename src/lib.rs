@@ -1,4 +1,11 @@
 #![doc = include_str!("../README.md")]
 
 pub mod api;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod session;
+mod telemetry;
+#[cfg(all(test, feature = "tracing"))]
+mod telemetry_test;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
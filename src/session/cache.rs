@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::CookieJar;
+use tokio::sync::OnceCell;
+
+use crate::session::clock::Clock;
+use crate::session::impersonation::ImpersonationAudit;
+use crate::session::principal::AnonymousId;
+use crate::session::session::{AccountStatus, Credential, SessionClaims, SessionManager};
+use crate::session::tenant::Tenant;
+use crate::session::validation::ClaimsValidation;
+
+pub const DEFAULT_ACCOUNT_TTL: Duration = Duration::from_secs(60);
+
+pub const DEFAULT_NEGATIVE_ACCOUNT_TTL: Duration = Duration::from_secs(10);
+
+pub const DEFAULT_MAX_CACHED_ACCOUNTS: usize = 10_000;
+
+/// A lookup in flight, shared by concurrent callers. Only found and missing accounts settle it, so
+/// on error the caller gets the inner manager's error as is, and a waiting caller looks up again.
+type Lookup<U> = Arc<OnceCell<Option<(U, AccountStatus)>>>;
+
+/// Cached accounts are keyed by tenant as well as account id.
+type AccountKey = (Option<Tenant>, String);
+
+struct CachedAccount<U> {
+    account: Option<(U, AccountStatus)>,
+    expires_at: SystemTime,
+}
+
+struct AccountCache<U> {
+    accounts: Mutex<HashMap<AccountKey, CachedAccount<U>>>,
+    lookups: Mutex<HashMap<AccountKey, Lookup<U>>>,
+}
+
+/// A `SessionManager` wrapper caching account lookups and their status by tenant and account id,
+/// so `resolve` doesn't hit the inner manager's storage on every request.
+///
+/// Both found and missing accounts are cached, each with their own ttl by the inner manager's
+/// clock, up to a maximum number of entries. Concurrent lookups for the same account are collapsed
+/// into a single call to the inner manager. Call `invalidate` when account data changes.
+pub struct CachedSessionManager<U, M> {
+    inner: M,
+    cache: Arc<AccountCache<U>>,
+    ttl: Duration,
+    negative_ttl: Duration,
+    max_entries: usize,
+}
+
+impl<U, M: Clone> Clone for CachedSessionManager<U, M> {
+    fn clone(&self) -> Self {
+        CachedSessionManager {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            ttl: self.ttl,
+            negative_ttl: self.negative_ttl,
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+impl<U: Clone, M> CachedSessionManager<U, M> {
+    pub fn new(inner: M) -> CachedSessionManager<U, M> {
+        CachedSessionManager {
+            inner,
+            cache: Arc::new(AccountCache {
+                accounts: Mutex::new(HashMap::new()),
+                lookups: Mutex::new(HashMap::new()),
+            }),
+            ttl: DEFAULT_ACCOUNT_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_ACCOUNT_TTL,
+            max_entries: DEFAULT_MAX_CACHED_ACCOUNTS,
+        }
+    }
+
+    /// Sets how long found accounts are cached.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long missing accounts are cached.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Drops the cached account in every tenant, so the next lookup goes to the inner manager. A
+    /// lookup in flight when the account is invalidated is not cached.
+    pub fn invalidate(&self, account_id: &str) {
+        self.cache
+            .lookups
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|(_, id), _| id != account_id);
+        self.cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|(_, id), _| id != account_id);
+    }
+
+    /// Drops the cached account in a single tenant.
+    pub fn invalidate_tenant_account(&self, tenant: &Tenant, account_id: &str) {
+        let key = (Some(tenant.clone()), account_id.to_string());
+        self.cache
+            .lookups
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&key);
+        self.cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&key);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.cache
+            .lookups
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+        self.cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn cached(&self, key: &AccountKey, now: SystemTime) -> Option<Option<(U, AccountStatus)>> {
+        self.cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(key)
+            .filter(|cached| cached.expires_at > now)
+            .map(|cached| cached.account.clone())
+    }
+
+    /// Caches the result of a lookup, unless the account was invalidated while it was in flight.
+    fn complete(
+        &self,
+        key: &AccountKey,
+        lookup: &Lookup<U>,
+        account: Option<&Option<(U, AccountStatus)>>,
+        now: SystemTime,
+    ) {
+        let mut lookups = self
+            .cache
+            .lookups
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match lookups.get(key) {
+            Some(current) if Arc::ptr_eq(current, lookup) => lookups.remove(key),
+            _ => return,
+        };
+
+        let (Some(account), true) = (account, self.max_entries > 0) else {
+            return;
+        };
+
+        let ttl = match account {
+            Some(_) => self.ttl,
+            None => self.negative_ttl,
+        };
+
+        let mut accounts = self
+            .cache
+            .accounts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if accounts.len() >= self.max_entries && !accounts.contains_key(key) {
+            accounts.retain(|_, cached| cached.expires_at > now);
+        }
+        if accounts.len() >= self.max_entries && !accounts.contains_key(key) {
+            let oldest = accounts
+                .iter()
+                .min_by_key(|(_, cached)| cached.expires_at)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                accounts.remove(&oldest);
+            }
+        }
+        accounts.insert(
+            key.clone(),
+            CachedAccount {
+                account: account.clone(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl<U, C, M> SessionManager<U, C> for CachedSessionManager<U, M>
+where
+    U: Clone + Send + Sync,
+    M: SessionManager<U, C>,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<C>> {
+        SessionManager::<U, C>::decode_claims(&self.inner, credential).await
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        Ok(SessionManager::<U, C>::get_account_status(self, account_id)
+            .await?
+            .map(|(account, _)| account))
+    }
+
+    async fn get_account_status(
+        &self,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        SessionManager::<U, C>::get_tenant_account(self, None, account_id).await
+    }
+
+    async fn get_tenant_account(
+        &self,
+        tenant: Option<&Tenant>,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        let key = (tenant.cloned(), account_id);
+        let clock = SessionManager::<U, C>::clock(&self.inner);
+        if let Some(account) = self.cached(&key, clock.now()) {
+            return Ok(account);
+        }
+
+        let lookup = self
+            .cache
+            .lookups
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = lookup
+            .get_or_try_init(|| {
+                SessionManager::<U, C>::get_tenant_account(&self.inner, tenant, key.1.clone())
+            })
+            .await;
+
+        self.complete(&key, &lookup, result.as_ref().ok().copied(), clock.now());
+
+        result.cloned()
+    }
+
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        SessionManager::<U, C>::extract_credential(&self.inner, request, cookies)
+    }
+
+    fn extract_credentials(
+        &self,
+        request: &Request,
+        cookies: &CookieJar,
+    ) -> Vec<(usize, Credential)> {
+        SessionManager::<U, C>::extract_credentials(&self.inner, request, cookies)
+    }
+
+    async fn decode_credentials(
+        &self,
+        credentials: Vec<(usize, Credential)>,
+    ) -> anyhow::Result<SessionClaims<C>> {
+        SessionManager::<U, C>::decode_credentials(&self.inner, credentials).await
+    }
+
+    async fn audit_impersonation(&self, audit: &ImpersonationAudit) -> anyhow::Result<()> {
+        SessionManager::<U, C>::audit_impersonation(&self.inner, audit).await
+    }
+
+    fn resolve_tenant(&self, request: &Request) -> Option<Tenant> {
+        SessionManager::<U, C>::resolve_tenant(&self.inner, request)
+    }
+
+    fn claims_validation(&self) -> &ClaimsValidation {
+        SessionManager::<U, C>::claims_validation(&self.inner)
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        SessionManager::<U, C>::clock(&self.inner)
+    }
+
+    fn max_impersonation_lifetime(&self) -> Duration {
+        SessionManager::<U, C>::max_impersonation_lifetime(&self.inner)
+    }
+
+    fn max_socket_ticket_lifetime(&self) -> Duration {
+        SessionManager::<U, C>::max_socket_ticket_lifetime(&self.inner)
+    }
+
+    fn anonymous_id(&self, request: &Request) -> Option<AnonymousId> {
+        SessionManager::<U, C>::anonymous_id(&self.inner, request)
+    }
+
+    fn allows_unverified(&self, request: &Request) -> bool {
+        SessionManager::<U, C>::allows_unverified(&self.inner, request)
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::CookieJar;
+use hyper::StatusCode;
+
+use crate::api::response::{JsonResponse, JsonStatus};
+use crate::session::cache::CachedSessionManager;
+use crate::session::clock::{Clock, MockClock};
+use crate::session::session::{Credential, SessionClaims, SessionManager};
+use crate::session::tenant::Tenant;
+
+#[derive(Clone, Debug, PartialEq)]
+struct FakeAccount {
+    name: String,
+}
+
+#[derive(Clone, Default)]
+struct CountingSessionManager {
+    accounts: Arc<Mutex<HashMap<String, FakeAccount>>>,
+    lookups: Arc<AtomicUsize>,
+    clock: MockClock,
+}
+
+impl CountingSessionManager {
+    fn with_account(self, account_id: &str, name: &str) -> Self {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(account_id.into(), FakeAccount { name: name.into() });
+        self
+    }
+
+    fn lookups(&self) -> usize {
+        self.lookups.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl SessionManager<FakeAccount> for CountingSessionManager {
+    async fn decode_claims(&self, _credential: Credential) -> anyhow::Result<SessionClaims> {
+        anyhow::bail!("Not implemented")
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if account_id == "broken-account-id" {
+            anyhow::bail!("Database unavailable");
+        }
+        Ok(self.accounts.lock().unwrap().get(&account_id).cloned())
+    }
+
+    fn extract_credential(&self, _request: &Request, _cookies: &CookieJar) -> Option<Credential> {
+        None
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &self.clock
+    }
+}
+
+fn account(name: &str) -> Option<FakeAccount> {
+    Some(FakeAccount { name: name.into() })
+}
+
+#[tokio::test]
+async fn test_caches_found_and_missing_accounts() {
+    let inner = CountingSessionManager::default().with_account("test-account-id", "Test Account");
+    let sessions = CachedSessionManager::new(inner.clone());
+
+    for _ in 0..3 {
+        assert_eq!(
+            sessions
+                .get_account("test-account-id".into())
+                .await
+                .unwrap(),
+            account("Test Account")
+        );
+        assert_eq!(
+            sessions
+                .get_account("unknown-account-id".into())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    assert_eq!(inner.lookups(), 2);
+    assert_eq!(sessions.len(), 2);
+}
+
+#[tokio::test]
+async fn test_entries_expire_by_the_inner_clock() {
+    let inner = CountingSessionManager::default().with_account("test-account-id", "Test Account");
+    let sessions = CachedSessionManager::new(inner.clone())
+        .with_ttl(Duration::from_millis(100))
+        .with_negative_ttl(Duration::ZERO);
+
+    sessions
+        .get_account("test-account-id".into())
+        .await
+        .unwrap();
+    sessions
+        .get_account("unknown-account-id".into())
+        .await
+        .unwrap();
+    sessions
+        .get_account("unknown-account-id".into())
+        .await
+        .unwrap();
+    assert_eq!(inner.lookups(), 3);
+
+    inner.clock.advance(Duration::from_millis(100));
+    sessions
+        .get_account("test-account-id".into())
+        .await
+        .unwrap();
+    assert_eq!(inner.lookups(), 4);
+}
+
+#[tokio::test]
+async fn test_concurrent_lookups_are_collapsed() {
+    let inner = CountingSessionManager::default().with_account("test-account-id", "Test Account");
+    let sessions = CachedSessionManager::new(inner.clone());
+
+    let lookups = (0..10).map(|_| {
+        let sessions = sessions.clone();
+        tokio::spawn(async move { sessions.get_account("test-account-id".into()).await })
+    });
+    for lookup in lookups.collect::<Vec<_>>() {
+        assert_eq!(lookup.await.unwrap().unwrap(), account("Test Account"));
+    }
+
+    assert_eq!(inner.lookups(), 1);
+}
+
+#[tokio::test]
+async fn test_errors_are_not_cached() {
+    let inner = CountingSessionManager::default();
+    let sessions = CachedSessionManager::new(inner.clone());
+
+    let err = sessions
+        .get_account("broken-account-id".into())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Database unavailable");
+    assert!(sessions
+        .get_account("broken-account-id".into())
+        .await
+        .is_err());
+
+    assert_eq!(inner.lookups(), 2);
+    assert!(sessions.is_empty());
+}
+
+#[tokio::test]
+async fn test_errors_keep_their_type() {
+    struct ForbiddenSessionManager;
+
+    #[async_trait]
+    impl SessionManager<FakeAccount> for ForbiddenSessionManager {
+        async fn decode_claims(&self, _credential: Credential) -> anyhow::Result<SessionClaims> {
+            anyhow::bail!("Not implemented")
+        }
+
+        async fn get_account(&self, _account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+            Err(JsonResponse::of_status(StatusCode::FORBIDDEN).anyhow())
+        }
+
+        fn extract_credential(
+            &self,
+            _request: &Request,
+            _cookies: &CookieJar,
+        ) -> Option<Credential> {
+            None
+        }
+    }
+
+    let sessions = CachedSessionManager::new(ForbiddenSessionManager);
+    let err = sessions
+        .get_account("test-account-id".into())
+        .await
+        .unwrap_err();
+
+    let status = err.downcast_ref::<JsonResponse<JsonStatus>>().unwrap();
+    assert_eq!(status.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_invalidate_drops_cached_account() {
+    let inner = CountingSessionManager::default().with_account("test-account-id", "Test Account");
+    let sessions = CachedSessionManager::new(inner.clone());
+
+    sessions
+        .get_account("test-account-id".into())
+        .await
+        .unwrap();
+    inner
+        .accounts
+        .lock()
+        .unwrap()
+        .get_mut("test-account-id")
+        .unwrap()
+        .name = "Renamed Account".into();
+    assert_eq!(
+        sessions
+            .get_account("test-account-id".into())
+            .await
+            .unwrap(),
+        account("Test Account")
+    );
+
+    sessions.invalidate("test-account-id");
+    assert_eq!(
+        sessions
+            .get_account("test-account-id".into())
+            .await
+            .unwrap(),
+        account("Renamed Account")
+    );
+
+    sessions.invalidate_all();
+    assert!(sessions.is_empty());
+    assert_eq!(inner.lookups(), 2);
+}
+
+#[tokio::test]
+async fn test_cache_is_bounded() {
+    let inner = CountingSessionManager::default();
+    let sessions = CachedSessionManager::new(inner.clone()).with_max_entries(2);
+
+    for account_id in ["first", "second", "third"] {
+        sessions.get_account(account_id.into()).await.unwrap();
+    }
+
+    assert_eq!(sessions.len(), 2);
+    sessions.get_account("third".into()).await.unwrap();
+    assert_eq!(inner.lookups(), 3);
+}
+
+#[tokio::test]
+async fn test_accounts_are_cached_per_tenant() {
+    let inner = CountingSessionManager::default().with_account("test-account-id", "Test Account");
+    let sessions = CachedSessionManager::new(inner.clone());
+    let acme = Tenant("acme".into());
+    let globex = Tenant("globex".into());
+
+    for tenant in [&acme, &globex, &acme, &globex] {
+        sessions
+            .get_tenant_account(Some(tenant), "test-account-id".into())
+            .await
+            .unwrap();
+    }
+    assert_eq!(inner.lookups(), 2);
+
+    sessions.invalidate_tenant_account(&acme, "test-account-id");
+    assert_eq!(sessions.len(), 1);
+    sessions.invalidate("test-account-id");
+    assert!(sessions.is_empty());
+}
@@ -0,0 +1,284 @@
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::CookieJar;
+
+use crate::session::clock::Clock;
+use crate::session::impersonation::ImpersonationAudit;
+use crate::session::principal::AnonymousId;
+use crate::session::session::{AccountStatus, Credential, SessionClaims, SessionManager};
+use crate::session::tenant::Tenant;
+use crate::session::validation::ClaimsValidation;
+use crate::telemetry;
+
+/// One way of authenticating a request in a `CredentialChain`, pairing a credential extractor with
+/// the decoder for its credentials.
+#[async_trait]
+pub trait CredentialSource<C = ()>: Send + Sync {
+    /// A name for the source in logs, such as `cookie` or `api_key`.
+    fn name(&self) -> &str;
+
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential>;
+
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<C>>;
+}
+
+/// A `CredentialSource` extracting and decoding credentials with an existing `SessionManager`.
+pub struct ManagerSource<U, M> {
+    name: String,
+    manager: M,
+    _account: PhantomData<fn() -> U>,
+}
+
+impl<U, M> ManagerSource<U, M> {
+    pub fn new(name: &str, manager: M) -> ManagerSource<U, M> {
+        ManagerSource {
+            name: name.into(),
+            manager,
+            _account: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<U, C, M> CredentialSource<C> for ManagerSource<U, M>
+where
+    M: SessionManager<U, C>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        self.manager.extract_credential(request, cookies)
+    }
+
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<C>> {
+        self.manager.decode_claims(credential).await
+    }
+}
+
+/// How a `CredentialChain` resolves a request carrying credentials for more than one source, such
+/// as both a cookie and an `authorization` header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CredentialConflict {
+    /// Decodes only the credential of the first source in the chain.
+    #[default]
+    PreferFirst,
+    /// Decodes each credential in chain order, using the first that decodes.
+    Fallthrough,
+    /// Rejects the request as unauthenticated.
+    Reject,
+    /// Decodes every credential, requiring each to decode to the same account and tenant.
+    RequireSameSubject,
+}
+
+/// A `SessionManager` trying several `CredentialSource`s in order, such as a browser cookie, a
+/// bearer JWT and an API key on the same routes, while looking up accounts with the inner manager.
+///
+/// `resolve` weighs the credentials of every source present on the request by the
+/// `CredentialConflict`. A credential passed on its own, such as a socket ticket, is decoded by the
+/// first source that accepts it.
+pub struct CredentialChain<M, C = ()> {
+    inner: M,
+    sources: Arc<Vec<Arc<dyn CredentialSource<C>>>>,
+    conflict: CredentialConflict,
+}
+
+impl<M: Clone, C> Clone for CredentialChain<M, C> {
+    fn clone(&self) -> Self {
+        CredentialChain {
+            inner: self.inner.clone(),
+            sources: self.sources.clone(),
+            conflict: self.conflict,
+        }
+    }
+}
+
+impl<M, C> CredentialChain<M, C> {
+    pub fn new(inner: M) -> CredentialChain<M, C> {
+        CredentialChain {
+            inner,
+            sources: Arc::new(Vec::new()),
+            conflict: CredentialConflict::default(),
+        }
+    }
+
+    /// Appends a source to the chain, tried after the sources before it.
+    pub fn with_source(mut self, source: impl CredentialSource<C> + 'static) -> Self {
+        let mut sources = self.sources.as_ref().clone();
+        sources.push(Arc::new(source));
+        self.sources = Arc::new(sources);
+        self
+    }
+
+    pub fn with_conflict(mut self, conflict: CredentialConflict) -> Self {
+        self.conflict = conflict;
+        self
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn decode_candidate(
+        &self,
+        (index, credential): (usize, Credential),
+    ) -> anyhow::Result<SessionClaims<C>> {
+        let source = self
+            .sources
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("Unknown credential source"))?;
+        source.decode_claims(credential).await.inspect_err(|_| {
+            telemetry::session_event(format_args!(
+                "Unable to decode {} credential.",
+                source.name()
+            ));
+        })
+    }
+}
+
+#[async_trait]
+impl<U, C, M> SessionManager<U, C> for CredentialChain<M, C>
+where
+    U: Send,
+    C: Send + Sync + 'static,
+    M: SessionManager<U, C>,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<C>> {
+        for source in self.sources.iter() {
+            if let Ok(claims) = source.decode_claims(credential.clone()).await {
+                return Ok(claims);
+            }
+        }
+        anyhow::bail!("No credential source could decode the credential")
+    }
+
+    async fn decode_credentials(
+        &self,
+        candidates: Vec<(usize, Credential)>,
+    ) -> anyhow::Result<SessionClaims<C>> {
+        match self.conflict {
+            _ if candidates.len() <= 1 => {}
+            CredentialConflict::Reject => {
+                telemetry::session_event(format_args!(
+                    "Rejected credentials from {} sources.",
+                    candidates.len()
+                ));
+                anyhow::bail!("Conflicting credentials");
+            }
+            CredentialConflict::RequireSameSubject => {
+                let mut decoded = Vec::with_capacity(candidates.len());
+                for candidate in candidates {
+                    decoded.push(self.decode_candidate(candidate).await?);
+                }
+                let first = decoded.remove(0);
+                if decoded
+                    .iter()
+                    .any(|claims| claims.sub != first.sub || claims.tenant != first.tenant)
+                {
+                    telemetry::session_event(format_args!(
+                        "Rejected credentials for different accounts."
+                    ));
+                    anyhow::bail!("Conflicting credentials");
+                }
+                return Ok(first);
+            }
+            CredentialConflict::Fallthrough => {
+                for candidate in candidates {
+                    if let Ok(claims) = self.decode_candidate(candidate).await {
+                        return Ok(claims);
+                    }
+                }
+                anyhow::bail!("No credential could be decoded");
+            }
+            CredentialConflict::PreferFirst => {}
+        }
+
+        match candidates.into_iter().next() {
+            Some(candidate) => self.decode_candidate(candidate).await,
+            None => anyhow::bail!("No credential"),
+        }
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        SessionManager::<U, C>::get_account(&self.inner, account_id).await
+    }
+
+    async fn get_account_status(
+        &self,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        SessionManager::<U, C>::get_account_status(&self.inner, account_id).await
+    }
+
+    async fn get_tenant_account(
+        &self,
+        tenant: Option<&Tenant>,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        SessionManager::<U, C>::get_tenant_account(&self.inner, tenant, account_id).await
+    }
+
+    /// The credential of the first source present on the request.
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        self.sources
+            .iter()
+            .find_map(|source| source.extract_credential(request, cookies))
+    }
+
+    fn extract_credentials(
+        &self,
+        request: &Request,
+        cookies: &CookieJar,
+    ) -> Vec<(usize, Credential)> {
+        self.sources
+            .iter()
+            .enumerate()
+            .filter_map(|(index, source)| {
+                source
+                    .extract_credential(request, cookies)
+                    .map(|credential| (index, credential))
+            })
+            .collect()
+    }
+
+    async fn audit_impersonation(&self, audit: &ImpersonationAudit) -> anyhow::Result<()> {
+        SessionManager::<U, C>::audit_impersonation(&self.inner, audit).await
+    }
+
+    fn resolve_tenant(&self, request: &Request) -> Option<Tenant> {
+        SessionManager::<U, C>::resolve_tenant(&self.inner, request)
+    }
+
+    fn claims_validation(&self) -> &ClaimsValidation {
+        SessionManager::<U, C>::claims_validation(&self.inner)
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        SessionManager::<U, C>::clock(&self.inner)
+    }
+
+    fn max_impersonation_lifetime(&self) -> Duration {
+        SessionManager::<U, C>::max_impersonation_lifetime(&self.inner)
+    }
+
+    fn max_socket_ticket_lifetime(&self) -> Duration {
+        SessionManager::<U, C>::max_socket_ticket_lifetime(&self.inner)
+    }
+
+    fn anonymous_id(&self, request: &Request) -> Option<AnonymousId> {
+        SessionManager::<U, C>::anonymous_id(&self.inner, request)
+    }
+
+    fn allows_unverified(&self, request: &Request) -> bool {
+        SessionManager::<U, C>::allows_unverified(&self.inner, request)
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use axum_extra::extract::CookieJar;
+use hyper::{header::HeaderName, StatusCode};
+
+use crate::session::chain::{CredentialChain, CredentialConflict, CredentialSource, ManagerSource};
+use crate::session::session::{authorize, resolve, Credential, SessionClaims};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+struct ApiKeySource;
+
+#[async_trait]
+impl CredentialSource for ApiKeySource {
+    fn name(&self) -> &str {
+        "api_key"
+    }
+
+    fn extract_credential(&self, request: &Request, _cookies: &CookieJar) -> Option<Credential> {
+        request
+            .headers()
+            .get("x-api-key")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| Credential(value.into()))
+    }
+
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        match credential.0.as_str() {
+            "service-key" => SessionClaims::new("service-account-id", Duration::from_secs(60), ()),
+            _ => anyhow::bail!("Unknown API key"),
+        }
+    }
+}
+
+type Chain = CredentialChain<FakeSessionManager<FakeAccount>>;
+
+fn accounts() -> FakeSessionManager<FakeAccount> {
+    FakeSessionManager::new()
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+        .with_account(
+            "service-account-id",
+            FakeAccount {
+                name: "Service Account".into(),
+            },
+        )
+}
+
+fn chain(conflict: CredentialConflict) -> Chain {
+    CredentialChain::new(accounts())
+        .with_source(ManagerSource::new("bearer", accounts()))
+        .with_source(ApiKeySource)
+        .with_conflict(conflict)
+}
+
+fn app(state: Chain) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move { caller.name }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, Chain>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, Chain>,
+            ))
+            .with_state(state),
+    )
+}
+
+fn api_key() -> HeaderName {
+    HeaderName::from_static("x-api-key")
+}
+
+#[tokio::test]
+async fn test_each_source_is_accepted() {
+    let session = accounts().mint_session("test-account-id");
+    let client = app(chain(CredentialConflict::PreferFirst));
+
+    let response = client.get("/api/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Test Account");
+
+    let response = client
+        .get("/api/account")
+        .header(api_key(), "service-key")
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Service Account");
+
+    client
+        .get("/api/account")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_prefer_first_uses_first_source() {
+    let session = accounts().mint_session("test-account-id");
+    let client = app(chain(CredentialConflict::PreferFirst));
+
+    let response = client
+        .get("/api/account")
+        .bearer(&session)
+        .header(api_key(), "service-key")
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Test Account");
+
+    client
+        .get("/api/account")
+        .bearer(&Credential("invalid".into()))
+        .header(api_key(), "service-key")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_fallthrough_uses_first_decoded_source() {
+    let client = app(chain(CredentialConflict::Fallthrough));
+
+    let response = client
+        .get("/api/account")
+        .bearer(&Credential("invalid".into()))
+        .header(api_key(), "service-key")
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Service Account");
+}
+
+#[tokio::test]
+async fn test_reject_rejects_multiple_credentials() {
+    let session = accounts().mint_session("test-account-id");
+    let client = app(chain(CredentialConflict::Reject));
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .header(api_key(), "service-key")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_require_same_subject_rejects_different_accounts() {
+    let client = app(chain(CredentialConflict::RequireSameSubject));
+
+    let session = accounts().mint_session("test-account-id");
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .header(api_key(), "service-key")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let session = accounts().mint_session("service-account-id");
+    let response = client
+        .get("/api/account")
+        .bearer(&session)
+        .header(api_key(), "service-key")
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Service Account");
+}
@@ -0,0 +1,71 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The source of the current time for issuing, validating and expiring sessions.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+
+    /// The current time in seconds since the Unix epoch.
+    fn now_secs(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+/// The system clock, the default everywhere a `Clock` is used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to, for testing expiry deterministically. Clones share the
+/// same time.
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock::new()
+    }
+}
+
+impl MockClock {
+    /// A clock stopped at the current system time.
+    pub fn new() -> MockClock {
+        MockClock::at(SystemTime::now())
+    }
+
+    pub fn at(now: SystemTime) -> MockClock {
+        MockClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap_or_else(|err| err.into_inner()) = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|err| err.into_inner()) += duration;
+    }
+
+    pub fn rewind(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|err| err.into_inner()) -= duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
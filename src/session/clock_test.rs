@@ -0,0 +1,224 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use hyper::StatusCode;
+use serde_json::json;
+
+use crate::session::clock::{Clock, MockClock};
+use crate::session::memory::InMemorySessionManager;
+use crate::session::session::{authorize, resolve, SessionClaims, SessionManager};
+use crate::session::store::{AccountLookup, MemorySessionStore, StoreSessionManager};
+use crate::session::validation::ClaimsValidation;
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+const NOW: u64 = 1_700_000_000;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+#[derive(Clone)]
+struct FakeAccounts;
+
+#[async_trait]
+impl AccountLookup<FakeAccount> for FakeAccounts {
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(match account_id.as_str() {
+            "test-account-id" => Some(FakeAccount {
+                name: "Test Account".into(),
+            }),
+            _ => None,
+        })
+    }
+}
+
+fn clock() -> MockClock {
+    MockClock::at(UNIX_EPOCH + Duration::from_secs(NOW))
+}
+
+fn app<S>(state: S) -> TestClient
+where
+    S: SessionManager<FakeAccount> + Clone + 'static,
+{
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move { caller.name }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, S>,
+            ))
+            .layer(from_fn_with_state(state.clone(), resolve::<FakeAccount, S>))
+            .with_state(state),
+    )
+}
+
+fn fake_sessions(clock: &MockClock) -> FakeSessionManager<FakeAccount> {
+    FakeSessionManager::new()
+        .with_clock(clock.clone())
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+}
+
+#[test]
+fn test_mock_clock_is_shared_between_clones() {
+    let clock = clock();
+    let other = clock.clone();
+
+    other.advance(Duration::from_secs(90));
+    assert_eq!(clock.now_secs(), NOW + 90);
+
+    clock.rewind(Duration::from_secs(30));
+    assert_eq!(other.now_secs(), NOW + 60);
+
+    let claims = SessionClaims::new_at(&clock, "test-account-id", Duration::from_secs(60), ())
+        .unwrap()
+        .with_not_before(Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(claims.iat, Some((NOW + 60) as usize));
+    assert_eq!(claims.nbf, Some((NOW + 70) as usize));
+    assert_eq!(claims.exp, (NOW + 120) as usize);
+}
+
+#[test]
+fn test_claims_are_issued_by_the_clock() {
+    let clock = clock();
+    let expires_in = Duration::from_secs(60);
+
+    for claims in [
+        SessionClaims::new_service_at(&clock, "test-account-id", expires_in).unwrap(),
+        SessionClaims::new_socket_ticket_at(&clock, "test-account-id", expires_in).unwrap(),
+        SessionClaims::new_impersonation_at(
+            &clock,
+            "support-account-id",
+            "test-account-id",
+            expires_in,
+        )
+        .unwrap(),
+    ] {
+        assert_eq!(claims.iat, Some(NOW as usize));
+        assert_eq!(claims.exp, (NOW + 60) as usize);
+    }
+
+    let claims = SessionClaims {
+        iat: None,
+        ..SessionClaims::new_at(&clock, "test-account-id", expires_in, ()).unwrap()
+    }
+    .with_not_before_at(&clock, Duration::from_secs(10))
+    .unwrap();
+    assert_eq!(claims.nbf, Some((NOW + 10) as usize));
+}
+
+#[tokio::test]
+async fn test_session_expires_by_the_clock_after_the_leeway() {
+    let clock = clock();
+    let sessions = fake_sessions(&clock);
+    let session = sessions.mint_session("test-account-id");
+    let client = app(sessions);
+
+    clock.advance(Duration::from_secs(3600 + 60));
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    clock.advance(Duration::from_secs(1));
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_leeway_tolerates_clock_skew_of_the_issuer() {
+    let clock = clock();
+    let sessions = fake_sessions(&clock)
+        .with_claims_validation(ClaimsValidation::new().with_leeway(Duration::from_secs(30)));
+    let session = sessions.mint_session("test-account-id");
+    let client = app(sessions);
+
+    clock.rewind(Duration::from_secs(30));
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    clock.rewind(Duration::from_secs(1));
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_sliding_expiry_refreshes_by_the_clock() {
+    let clock = clock();
+    let store = MemorySessionStore::new().with_clock(clock.clone());
+    let sessions =
+        StoreSessionManager::new(store, FakeAccounts, "session", Duration::from_secs(60))
+            .with_sliding_expiry()
+            .with_clock(clock.clone());
+    let (session, _) = sessions
+        .create_session("test-account-id", json!(null))
+        .await
+        .unwrap();
+    let client = app(sessions);
+
+    for _ in 0..3 {
+        clock.advance(Duration::from_secs(50));
+        client
+            .get("/api/account")
+            .cookie("session", &session.id)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    clock.advance(Duration::from_secs(61));
+    client
+        .get("/api/account")
+        .cookie("session", &session.id)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_in_memory_sessions_are_issued_and_expired_by_the_clock() {
+    let clock = clock();
+    let sessions = InMemorySessionManager::new(b"test-key").with_clock(clock.clone());
+    let session = sessions
+        .issue_session("test-account-id", Duration::from_secs(60))
+        .unwrap();
+
+    let claims = SessionManager::<FakeAccount>::decode_claims(&sessions, session.clone())
+        .await
+        .unwrap();
+    assert_eq!(claims.iat, Some(NOW as usize));
+    assert_eq!(claims.exp, (NOW + 60) as usize);
+
+    clock.advance(Duration::from_secs(61));
+    assert!(
+        SessionManager::<FakeAccount>::decode_claims(&sessions, session)
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use axum::{
+    extract::Request,
+    http::{Method, StatusCode},
+    middleware::Next,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::request_id::RequestId;
+use crate::api::response::{JsonResponse, ResponseError};
+use crate::session::tenant::Tenant;
+use crate::telemetry;
+
+/// The default cap on the lifetime of an impersonated session, from its `iat` to its `exp`.
+/// Applied when issuing the session, while `resolve` rejects impersonated sessions without an `iat`
+/// or living longer than `SessionManager::max_impersonation_lifetime`.
+pub const MAX_IMPERSONATION_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+pub const IMPERSONATION_FORBIDDEN_DETAIL: &str = "Impersonated sessions are not allowed.";
+
+/// The real actor of an impersonated session, as the `act` claim of RFC 8693. Inserted into the
+/// request extensions by `resolve` for impersonated requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+}
+
+/// The account of the real actor of an impersonated session, inserted into the request extensions
+/// by `resolve` alongside the impersonated account.
+#[derive(Clone, Debug)]
+pub struct Impersonator<U>(pub U);
+
+/// A request made with an impersonated session, passed to `SessionManager::audit_impersonation`.
+#[derive(Clone, Debug)]
+pub struct ImpersonationAudit {
+    pub actor_id: String,
+    pub account_id: String,
+    pub tenant: Option<Tenant>,
+    pub method: Method,
+    pub path: String,
+    pub request_id: Option<RequestId>,
+}
+
+impl ImpersonationAudit {
+    pub(crate) fn of(
+        actor: &Actor,
+        account_id: &str,
+        tenant: Option<&Tenant>,
+        request: &Request,
+    ) -> ImpersonationAudit {
+        ImpersonationAudit {
+            actor_id: actor.sub.clone(),
+            account_id: account_id.into(),
+            tenant: tenant.cloned(),
+            method: request.method().clone(),
+            path: request.uri().path().into(),
+            request_id: RequestId::current(),
+        }
+    }
+}
+
+/// Middleware rejecting impersonated requests with a 403 response, for routes such as changing
+/// credentials that only the account holder may use. Layer it inside `resolve`.
+pub async fn forbid_impersonation(
+    request: Request,
+    next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    if let Some(actor) = request.extensions().get::<Actor>() {
+        telemetry::session_event(format_args!(
+            "Forbidden! Route does not allow impersonation by {}.",
+            actor.sub
+        ));
+        return Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+            .with_detail(IMPERSONATION_FORBIDDEN_DETAIL)
+            .into());
+    }
+
+    Ok(next.run(request).await)
+}
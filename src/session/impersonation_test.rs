@@ -0,0 +1,227 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::{routing::get, Extension, Router};
+use hyper::{Method, StatusCode};
+
+use crate::session::clock::{Clock, MockClock, SystemClock};
+use crate::session::impersonation::{
+    forbid_impersonation, Actor, Impersonator, IMPERSONATION_FORBIDDEN_DETAIL,
+    MAX_IMPERSONATION_LIFETIME,
+};
+use crate::session::session::{authorize, resolve, AccountStatus, SessionClaims};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+fn account(name: &str) -> FakeAccount {
+    FakeAccount { name: name.into() }
+}
+
+fn sessions() -> FakeSessionManager<FakeAccount> {
+    FakeSessionManager::new()
+        .with_account("test-account-id", account("Test Account"))
+        .with_account("support-account-id", account("Support Account"))
+}
+
+fn app(state: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(
+                    |Extension(caller): Extension<FakeAccount>,
+                     impersonator: Option<Extension<Impersonator<FakeAccount>>>| async move {
+                        match impersonator {
+                            Some(Extension(Impersonator(actor))) => {
+                                format!("Hello, {} as {}!", actor.name, caller.name)
+                            }
+                            None => format!("Hello, {}!", caller.name),
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/api/account/password",
+                get(|| async { "Password changed!" }).route_layer(from_fn(forbid_impersonation)),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[test]
+fn test_impersonation_lifetime_is_capped() {
+    let claims = SessionClaims::new_impersonation(
+        "support-account-id",
+        "test-account-id",
+        Duration::MAX / 2,
+    )
+    .unwrap();
+
+    assert_eq!(
+        claims.act,
+        Some(Actor {
+            sub: "support-account-id".into()
+        })
+    );
+    assert!(claims.exp as u64 <= SystemClock.now_secs() + MAX_IMPERSONATION_LIFETIME.as_secs());
+}
+
+#[tokio::test]
+async fn test_impersonated_request_exposes_both_accounts_and_is_audited() {
+    let sessions = sessions();
+    let session = sessions.mint_impersonation_session("support-account-id", "test-account-id");
+    let client = app(sessions.clone());
+
+    let response = client.get("/api/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Support Account as Test Account!");
+
+    let audits = sessions.impersonation_audits();
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].actor_id, "support-account-id");
+    assert_eq!(audits[0].account_id, "test-account-id");
+    assert_eq!(audits[0].method, Method::GET);
+    assert_eq!(audits[0].path, "/api/account");
+}
+
+#[tokio::test]
+async fn test_forbidden_route_rejects_impersonated_request() {
+    let sessions = sessions();
+    let impersonated = sessions.mint_impersonation_session("support-account-id", "test-account-id");
+    let session = sessions.mint_session("test-account-id");
+    let client = app(sessions);
+
+    client
+        .get("/api/account/password")
+        .bearer(&impersonated)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(IMPERSONATION_FORBIDDEN_DETAIL));
+
+    client
+        .get("/api/account/password")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_impersonation_beyond_lifetime_cap_is_rejected() {
+    let sessions = sessions();
+    let claims = SessionClaims {
+        act: Some(Actor {
+            sub: "support-account-id".into(),
+        }),
+        ..SessionClaims::new("test-account-id", Duration::from_secs(3600), ()).unwrap()
+    };
+    let session = FakeSessionManager::<FakeAccount>::encode_claims(&claims);
+    let client = app(sessions.clone());
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert!(sessions.impersonation_audits().is_empty());
+}
+
+#[tokio::test]
+async fn test_impersonation_lifetime_cap_is_configurable() {
+    let sessions = sessions().with_max_impersonation_lifetime(Duration::from_secs(3600));
+    let claims = SessionClaims {
+        act: Some(Actor {
+            sub: "support-account-id".into(),
+        }),
+        ..SessionClaims::new("test-account-id", Duration::from_secs(3600), ()).unwrap()
+    };
+    let session = FakeSessionManager::<FakeAccount>::encode_claims(&claims);
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_long_lived_impersonation_is_rejected_near_its_expiry() {
+    let clock = MockClock::at(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    let sessions = sessions().with_clock(clock.clone());
+    let claims = SessionClaims {
+        act: Some(Actor {
+            sub: "support-account-id".into(),
+        }),
+        ..SessionClaims::new_at(&clock, "test-account-id", Duration::from_secs(3600), ()).unwrap()
+    };
+    let session = FakeSessionManager::<FakeAccount>::encode_claims(&claims);
+    let client = app(sessions.clone());
+
+    clock.advance(Duration::from_secs(3600) - MAX_IMPERSONATION_LIFETIME / 2);
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert!(sessions.impersonation_audits().is_empty());
+}
+
+#[tokio::test]
+async fn test_impersonation_without_issue_time_is_rejected() {
+    let sessions = sessions();
+    let claims = SessionClaims {
+        act: Some(Actor {
+            sub: "support-account-id".into(),
+        }),
+        iat: None,
+        ..SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap()
+    };
+    let session = FakeSessionManager::<FakeAccount>::encode_claims(&claims);
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_impersonation_by_inactive_actor_is_rejected() {
+    let sessions = sessions();
+    sessions.insert_account_with_status(
+        "support-account-id",
+        account("Support Account"),
+        AccountStatus::Suspended,
+    );
+    let session = sessions.mint_impersonation_session("support-account-id", "test-account-id");
+    let unknown = sessions.mint_impersonation_session("unknown-account-id", "test-account-id");
+    let client = app(sessions);
+
+    for session in [session, unknown] {
+        client
+            .get("/api/account")
+            .bearer(&session)
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+}
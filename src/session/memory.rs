@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::CookieJar;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::session::{Credential, SessionClaims, SessionManager};
+use crate::session::token::{decode_hs256, encode_hs256};
+
+/// A ready-to-use `SessionManager` for development and integration tests, holding accounts in
+/// memory and issuing sessions as signed HS256 JWTs. Sessions can be revoked before they expire.
+pub struct InMemorySessionManager<U> {
+    key: Arc<[u8]>,
+    cookie_name: Option<String>,
+    accounts: Arc<RwLock<HashMap<String, U>>>,
+    revoked: Arc<RwLock<HashMap<String, usize>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<U> Clone for InMemorySessionManager<U> {
+    fn clone(&self) -> Self {
+        InMemorySessionManager {
+            key: self.key.clone(),
+            cookie_name: self.cookie_name.clone(),
+            accounts: self.accounts.clone(),
+            revoked: self.revoked.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+fn digest(credential: &Credential) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(credential.0.as_bytes()))
+}
+
+impl<U> InMemorySessionManager<U> {
+    pub fn new(key: &[u8]) -> InMemorySessionManager<U> {
+        InMemorySessionManager {
+            key: Arc::from(key),
+            cookie_name: None,
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+            revoked: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Also accepts the session from the named cookie, when no `authorization` header is present.
+    pub fn with_cookie(mut self, cookie_name: &str) -> Self {
+        self.cookie_name = Some(cookie_name.into());
+        self
+    }
+
+    /// Issues and validates sessions by the clock, instead of `SystemClock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn insert_account(&self, account_id: &str, account: U) {
+        self.accounts
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(account_id.into(), account);
+    }
+
+    pub fn remove_account(&self, account_id: &str) -> Option<U> {
+        self.accounts
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(account_id)
+    }
+
+    pub fn issue_session(
+        &self,
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<Credential> {
+        let claims = SessionClaims::new_at(self.clock.as_ref(), account_id, expires_in, ())?;
+        Ok(Credential(encode_hs256(&claims, &self.key)?))
+    }
+
+    /// Revokes a session, rejecting it until it expires. Expired revocations are pruned.
+    pub fn revoke_session(&self, credential: &Credential) -> anyhow::Result<()> {
+        let claims: SessionClaims = decode_hs256(&credential.0, &self.key)?;
+        let now = self.clock.now_secs();
+
+        let mut revoked = self.revoked.write().unwrap_or_else(|err| err.into_inner());
+        revoked.retain(|_, exp| *exp as u64 >= now);
+        revoked.insert(digest(credential), claims.exp);
+
+        Ok(())
+    }
+
+    pub fn is_revoked(&self, credential: &Credential) -> bool {
+        self.revoked
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .contains_key(&digest(credential))
+    }
+}
+
+#[async_trait]
+impl<U> SessionManager<U> for InMemorySessionManager<U>
+where
+    U: Clone + Send + Sync,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        let claims: SessionClaims = decode_hs256(&credential.0, &self.key)?;
+
+        if (claims.exp as u64) < self.clock.now_secs() {
+            anyhow::bail!("Session expired");
+        }
+        if self.is_revoked(&credential) {
+            anyhow::bail!("Session revoked");
+        }
+
+        Ok(claims)
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        Ok(self
+            .accounts
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(&account_id)
+            .cloned())
+    }
+
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        Credential::from_authorization_header(request).or_else(|| {
+            self.cookie_name
+                .as_deref()
+                .and_then(|cookie_name| Credential::from_cookie(cookie_name, cookies))
+        })
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
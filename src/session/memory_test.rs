@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use hyper::StatusCode;
+
+use crate::session::memory::InMemorySessionManager;
+use crate::session::session::{authorize, resolve, Credential, SessionClaims};
+use crate::session::token::encode_hs256;
+use crate::testing::client::TestClient;
+
+const KEY: &[u8] = b"test-signing-key";
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+fn sessions() -> InMemorySessionManager<FakeAccount> {
+    let sessions = InMemorySessionManager::new(KEY).with_cookie("session");
+    sessions.insert_account(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+    );
+    sessions
+}
+
+fn app(state: InMemorySessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move {
+                    format!("Hello, {}!", caller.name)
+                }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, InMemorySessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, InMemorySessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[tokio::test]
+async fn test_issued_session_is_accepted_from_header_and_cookie() {
+    let sessions = sessions();
+    let session = sessions
+        .issue_session("test-account-id", Duration::from_secs(60))
+        .unwrap();
+    let client = app(sessions);
+
+    let response = client.get("/api/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Test Account!");
+
+    client
+        .get("/api/account")
+        .session_cookie("session", &session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_revoked_session_is_rejected() {
+    let sessions = sessions();
+    let revoked = sessions
+        .issue_session("test-account-id", Duration::from_secs(60))
+        .unwrap();
+    let other = sessions
+        .issue_session("test-account-id", Duration::from_secs(120))
+        .unwrap();
+    sessions.revoke_session(&revoked).unwrap();
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .bearer(&revoked)
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+
+    client
+        .get("/api/account")
+        .bearer(&other)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_expired_session_is_rejected() {
+    let mut claims = SessionClaims::new("test-account-id", Duration::ZERO, ()).unwrap();
+    claims.exp -= 10;
+    let expired = Credential(encode_hs256(&claims, KEY).unwrap());
+
+    app(sessions())
+        .get("/api/account")
+        .bearer(&expired)
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+}
+
+#[tokio::test]
+async fn test_session_signed_with_other_key_is_rejected() {
+    let forged = InMemorySessionManager::<FakeAccount>::new(b"other-key")
+        .issue_session("test-account-id", Duration::from_secs(60))
+        .unwrap();
+
+    app(sessions())
+        .get("/api/account")
+        .bearer(&forged)
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+}
+
+#[tokio::test]
+async fn test_removed_account_is_rejected() {
+    let sessions = sessions();
+    let session = sessions
+        .issue_session("test-account-id", Duration::from_secs(60))
+        .unwrap();
+    sessions.remove_account("test-account-id");
+
+    app(sessions)
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+}
@@ -1,3 +1,41 @@
+pub mod cache;
+#[cfg(test)]
+mod cache_test;
+pub mod chain;
+#[cfg(test)]
+mod chain_test;
+pub mod clock;
+#[cfg(test)]
+mod clock_test;
+pub mod impersonation;
+#[cfg(test)]
+mod impersonation_test;
+pub mod memory;
+#[cfg(test)]
+mod memory_test;
+pub mod principal;
+#[cfg(test)]
+mod principal_test;
+pub mod purpose;
+#[cfg(test)]
+mod purpose_test;
+#[allow(clippy::module_inception)]
 pub mod session;
 #[cfg(test)]
 pub mod session_test;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(all(test, feature = "sqlite"))]
+mod sqlite_test;
+pub mod store;
+#[cfg(test)]
+mod store_test;
+pub mod tenant;
+#[cfg(test)]
+mod tenant_test;
+pub mod token;
+#[cfg(test)]
+mod token_test;
+pub mod validation;
+#[cfg(test)]
+mod validation_test;
@@ -0,0 +1,144 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use hmac::Mac;
+use hyper::{http::request::Parts, http::Extensions, StatusCode};
+
+use crate::api::response::{JsonResponse, ResponseError};
+use crate::session::session::{verification_required, VERIFICATION_REQUIRED_DETAIL};
+use crate::session::token::sign;
+
+pub const USER_REQUIRED_DETAIL: &str = "User caller required.";
+
+pub const SERVICE_REQUIRED_DETAIL: &str = "Service caller required.";
+
+/// Whether an authenticated caller is a human user, from session claims, or a service account,
+/// from service claims.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrincipalKind {
+    User,
+    Service,
+}
+
+/// A stable, pseudonymous identifier for an anonymous client, such as for rate limiting. Keyed
+/// with an application secret, so the client address can't be recovered by hashing every address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AnonymousId(pub String);
+
+impl AnonymousId {
+    /// Derives the identifier from the client IP address when the router is served with
+    /// `ConnectInfo<SocketAddr>`, or none otherwise.
+    pub fn of(extensions: &Extensions, secret: &[u8]) -> Option<AnonymousId> {
+        let ConnectInfo(addr) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+        AnonymousId::of_client(secret, &addr.ip().to_string())
+    }
+
+    /// Derives the identifier from a client key, such as an IP address taken from a trusted
+    /// proxy header.
+    pub fn of_client(secret: &[u8], key: &str) -> Option<AnonymousId> {
+        let digest = sign(secret, key).ok()?.finalize().into_bytes();
+        let digest: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Some(AnonymousId(digest[..32].into()))
+    }
+}
+
+/// The caller of a request, inserted into the request extensions by `resolve`. Anonymous callers
+/// carry the id from `SessionManager::anonymous_id`, if any.
+#[derive(Clone, Debug)]
+pub enum Principal<U> {
+    User(U),
+    Service(U),
+    Anonymous(Option<AnonymousId>),
+}
+
+impl<U> Principal<U> {
+    pub fn kind(&self) -> Option<PrincipalKind> {
+        match self {
+            Principal::User(_) => Some(PrincipalKind::User),
+            Principal::Service(_) => Some(PrincipalKind::Service),
+            Principal::Anonymous(_) => None,
+        }
+    }
+
+    pub fn account(&self) -> Option<&U> {
+        match self {
+            Principal::User(account) | Principal::Service(account) => Some(account),
+            Principal::Anonymous(_) => None,
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Principal::Anonymous(_))
+    }
+}
+
+impl<U, S> FromRequestParts<S> for Principal<U>
+where
+    U: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<Principal<U>>()
+            .cloned()
+            .unwrap_or(Principal::Anonymous(None)))
+    }
+}
+
+fn require<U: Clone + Send + Sync + 'static>(
+    parts: &Parts,
+    kind: PrincipalKind,
+) -> Result<U, ResponseError> {
+    let principal = parts.extensions.get::<Principal<U>>();
+
+    match principal.and_then(|principal| principal.account().map(|account| (principal, account))) {
+        Some(_) if verification_required(&parts.extensions) => {
+            Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                .with_detail(VERIFICATION_REQUIRED_DETAIL)
+                .into())
+        }
+        Some((principal, account)) if principal.kind() == Some(kind) => Ok(account.clone()),
+        Some(_) => Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+            .with_detail(match kind {
+                PrincipalKind::User => USER_REQUIRED_DETAIL,
+                PrincipalKind::Service => SERVICE_REQUIRED_DETAIL,
+            })
+            .into()),
+        None => Err(JsonResponse::of_status(StatusCode::UNAUTHORIZED).into()),
+    }
+}
+
+/// Extracts the account of a user caller, rejecting anonymous callers with a 401 response, and
+/// service callers and unverified accounts, like `authorize_user`, with a 403 response.
+pub struct UserCaller<U>(pub U);
+
+impl<U, S> FromRequestParts<S> for UserCaller<U>
+where
+    U: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ResponseError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        require(parts, PrincipalKind::User).map(UserCaller)
+    }
+}
+
+/// Extracts the account of a service caller, rejecting anonymous callers with a 401 response, and
+/// user callers and unverified accounts, like `authorize_service`, with a 403 response.
+pub struct ServiceCaller<U>(pub U);
+
+impl<U, S> FromRequestParts<S> for ServiceCaller<U>
+where
+    U: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = ResponseError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        require(parts, PrincipalKind::Service).map(ServiceCaller)
+    }
+}
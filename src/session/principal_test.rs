@@ -0,0 +1,232 @@
+use std::net::SocketAddr;
+
+use axum::extract::ConnectInfo;
+use axum::http::Extensions;
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Router};
+use hyper::StatusCode;
+use sha2::{Digest, Sha256};
+
+use crate::session::principal::{
+    AnonymousId, Principal, ServiceCaller, UserCaller, SERVICE_REQUIRED_DETAIL,
+    USER_REQUIRED_DETAIL,
+};
+use crate::session::session::{
+    authorize_service, authorize_user, resolve, AccountStatus, VERIFICATION_REQUIRED_DETAIL,
+};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+const SECRET: &[u8] = b"test-anonymous-secret";
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+type Sessions = FakeSessionManager<FakeAccount>;
+
+fn sessions() -> Sessions {
+    FakeSessionManager::new()
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+        .with_account(
+            "service-account-id",
+            FakeAccount {
+                name: "Service Account".into(),
+            },
+        )
+}
+
+fn app(state: Sessions) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/caller",
+                get(|principal: Principal<FakeAccount>| async move {
+                    match principal {
+                        Principal::User(account) => format!("User {}", account.name),
+                        Principal::Service(account) => format!("Service {}", account.name),
+                        Principal::Anonymous(Some(AnonymousId(id))) => format!("Anonymous {}", id),
+                        Principal::Anonymous(None) => "Anonymous".into(),
+                    }
+                }),
+            )
+            .route(
+                "/api/user",
+                get(|UserCaller(caller): UserCaller<FakeAccount>| async move { caller.name }),
+            )
+            .route(
+                "/api/service",
+                get(|ServiceCaller(caller): ServiceCaller<FakeAccount>| async move { caller.name }),
+            )
+            .route(
+                "/api/user-only",
+                get(|| async { "Hello, user!" }).route_layer(from_fn_with_state(
+                    state.clone(),
+                    authorize_user::<FakeAccount, Sessions>,
+                )),
+            )
+            .route(
+                "/api/service-only",
+                get(|| async { "Hello, service!" }).route_layer(from_fn_with_state(
+                    state.clone(),
+                    authorize_service::<FakeAccount, Sessions>,
+                )),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, Sessions>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[tokio::test]
+async fn test_principal_distinguishes_callers() {
+    let sessions = sessions();
+    let user = sessions.mint_session("test-account-id");
+    let service = sessions.mint_service_session("service-account-id");
+    let client = app(sessions);
+
+    let response = client.get("/api/caller").bearer(&user).send().await;
+    assert_eq!(response.text(), "User Test Account");
+
+    let response = client.get("/api/caller").bearer(&service).send().await;
+    assert_eq!(response.text(), "Service Service Account");
+
+    let response = client.get("/api/caller").send().await;
+    assert_eq!(response.text(), "Anonymous");
+}
+
+#[tokio::test]
+async fn test_caller_extractors_require_kind() {
+    let sessions = sessions();
+    let user = sessions.mint_session("test-account-id");
+    let service = sessions.mint_service_session("service-account-id");
+    let client = app(sessions);
+
+    let response = client.get("/api/user").bearer(&user).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Test Account");
+    client
+        .get("/api/user")
+        .bearer(&service)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(USER_REQUIRED_DETAIL));
+    client
+        .get("/api/user")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = client.get("/api/service").bearer(&service).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Service Account");
+    client
+        .get("/api/service")
+        .bearer(&user)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(SERVICE_REQUIRED_DETAIL));
+}
+
+#[tokio::test]
+async fn test_caller_extractors_reject_unverified_accounts() {
+    let sessions = sessions();
+    sessions.insert_account_with_status(
+        "unverified-account-id",
+        FakeAccount {
+            name: "Unverified Account".into(),
+        },
+        AccountStatus::Unverified,
+    );
+    let user = sessions.mint_session("unverified-account-id");
+    let service = sessions.mint_service_session("unverified-account-id");
+    let client = app(sessions);
+
+    for (path, session) in [("/api/user", user), ("/api/service", service)] {
+        client
+            .get(path)
+            .bearer(&session)
+            .send()
+            .await
+            .assert_status_body(StatusCode::FORBIDDEN, Some(VERIFICATION_REQUIRED_DETAIL));
+    }
+}
+
+#[tokio::test]
+async fn test_authorize_variants_require_kind() {
+    let sessions = sessions();
+    let user = sessions.mint_session("test-account-id");
+    let service = sessions.mint_service_session("service-account-id");
+    let client = app(sessions);
+
+    client
+        .get("/api/user-only")
+        .bearer(&user)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    client
+        .get("/api/user-only")
+        .bearer(&service)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(USER_REQUIRED_DETAIL));
+    client
+        .get("/api/service-only")
+        .bearer(&service)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    client
+        .get("/api/service-only")
+        .bearer(&user)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(SERVICE_REQUIRED_DETAIL));
+    client
+        .get("/api/service-only")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_anonymous_id_is_stable_per_client() {
+    let extensions = |addr: &str| {
+        let mut extensions = Extensions::new();
+        extensions.insert(ConnectInfo(addr.parse::<SocketAddr>().unwrap()));
+        extensions
+    };
+
+    let first = AnonymousId::of(&extensions("203.0.113.7:5000"), SECRET).unwrap();
+    assert_eq!(
+        Some(&first),
+        AnonymousId::of(&extensions("203.0.113.7:6000"), SECRET).as_ref()
+    );
+    assert_ne!(
+        Some(&first),
+        AnonymousId::of(&extensions("203.0.113.8:5000"), SECRET).as_ref()
+    );
+    assert_eq!(first.0.len(), 32);
+    assert!(!first.0.contains("203.0.113.7"));
+    assert_eq!(AnonymousId::of(&Extensions::new(), SECRET), None);
+}
+
+#[test]
+fn test_anonymous_id_is_keyed() {
+    let first = AnonymousId::of_client(SECRET, "203.0.113.7").unwrap();
+
+    assert_ne!(
+        Some(first.clone()),
+        AnonymousId::of_client(b"other-secret", "203.0.113.7")
+    );
+    assert!(!format!("{:x}", Sha256::digest("203.0.113.7")).starts_with(&first.0));
+}
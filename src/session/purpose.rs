@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::Mac;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::session::{
+    SessionClaims, SERVICE_CLAIMS_TYPE, SESSION_CLAIMS_TYPE, SOCKET_TICKET_CLAIMS_TYPE,
+};
+use crate::session::token::{decode_hs256, encode_hs256, sign};
+use crate::session::validation::{ClaimsError, ClaimsValidation};
+
+/// What a purpose token may be used for, as its `omn_cl_typ`, and how long it lives. Tokens of one
+/// purpose are rejected for any other, and by `resolve`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenPurpose {
+    pub claims_type: &'static str,
+    pub ttl: Duration,
+}
+
+impl TokenPurpose {
+    pub const EMAIL_VERIFICATION: TokenPurpose =
+        TokenPurpose::new("email_verification", Duration::from_secs(24 * 60 * 60));
+
+    pub const PASSWORD_RESET: TokenPurpose =
+        TokenPurpose::new("password_reset", Duration::from_secs(60 * 60));
+
+    pub const INVITATION: TokenPurpose =
+        TokenPurpose::new("invitation", Duration::from_secs(7 * 24 * 60 * 60));
+
+    pub const MAGIC_LINK: TokenPurpose =
+        TokenPurpose::new("magic_link", Duration::from_secs(15 * 60));
+
+    /// A purpose with its own claims type, which must not be `session`, `service` or
+    /// `socket_ticket`.
+    pub const fn new(claims_type: &'static str, ttl: Duration) -> TokenPurpose {
+        TokenPurpose { claims_type, ttl }
+    }
+
+    pub const fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn is_reserved(&self) -> bool {
+        [
+            SESSION_CLAIMS_TYPE,
+            SERVICE_CLAIMS_TYPE,
+            SOCKET_TICKET_CLAIMS_TYPE,
+        ]
+        .contains(&self.claims_type)
+    }
+}
+
+/// The claims of a purpose token besides the registered ones, carried as `SessionClaims` custom
+/// claims.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PurposeClaims<C = ()> {
+    /// A keyed hash of the value the token is bound to, such as the current password hash or
+    /// email.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bnd: Option<String>,
+    #[serde(flatten)]
+    pub custom: C,
+}
+
+/// Context deriving the binding key from the token key, so binding hashes are never computed
+/// under the key signing the tokens.
+const BINDING_KEY_CONTEXT: &str = "omn-binding";
+
+/// Hashes a value a purpose token is bound to with HMAC-SHA256 under a key derived from the token
+/// key, so the value can't be read from the token, nor guessed by hashing candidates without the
+/// key.
+fn binding_hash(key: &[u8], binding: &str) -> anyhow::Result<String> {
+    let binding_key = sign(key, BINDING_KEY_CONTEXT)?.finalize().into_bytes();
+    Ok(URL_SAFE_NO_PAD.encode(sign(&binding_key, binding)?.finalize().into_bytes()))
+}
+
+/// Why a purpose token was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PurposeTokenError {
+    /// The token is malformed, has an invalid signature or has no `jti`.
+    Invalid,
+    WrongPurpose,
+    Claims(ClaimsError),
+    /// The value the token is bound to has changed since it was issued.
+    Stale,
+    AlreadyUsed,
+}
+
+impl std::fmt::Display for PurposeTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurposeTokenError::Invalid => write!(f, "Token is invalid"),
+            PurposeTokenError::WrongPurpose => write!(f, "Token was issued for another purpose"),
+            PurposeTokenError::Claims(err) => write!(f, "{}", err),
+            PurposeTokenError::Stale => write!(f, "Token is bound to a value that has changed"),
+            PurposeTokenError::AlreadyUsed => write!(f, "Token was already used"),
+        }
+    }
+}
+
+impl std::error::Error for PurposeTokenError {}
+
+/// Records the `jti` of consumed purpose tokens until they expire, to enforce single use.
+#[async_trait]
+pub trait ConsumedTokenStore: Send + Sync {
+    /// Marks the token consumed until `expires_at`, in seconds since the Unix epoch, returning
+    /// whether it wasn't consumed before. Must be atomic.
+    async fn consume(&self, jti: &str, expires_at: u64) -> anyhow::Result<bool>;
+}
+
+/// In-memory `ConsumedTokenStore`, pruning expired tokens as new tokens are consumed.
+#[derive(Clone)]
+pub struct MemoryConsumedTokenStore {
+    consumed: Arc<RwLock<HashMap<String, u64>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MemoryConsumedTokenStore {
+    fn default() -> Self {
+        MemoryConsumedTokenStore {
+            consumed: Arc::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl MemoryConsumedTokenStore {
+    pub fn new() -> MemoryConsumedTokenStore {
+        MemoryConsumedTokenStore::default()
+    }
+
+    /// Prunes expired tokens by the clock, instead of `SystemClock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+#[async_trait]
+impl ConsumedTokenStore for MemoryConsumedTokenStore {
+    async fn consume(&self, jti: &str, expires_at: u64) -> anyhow::Result<bool> {
+        let now = self.clock.now_secs();
+
+        let mut consumed = self.consumed.write().unwrap_or_else(|err| err.into_inner());
+        consumed.retain(|_, expires_at| *expires_at >= now);
+        Ok(consumed.insert(jti.into(), expires_at).is_none())
+    }
+}
+
+/// Issues and verifies purpose-bound tokens, such as for email verification or password reset, as
+/// HS256-signed `SessionClaims` with the purpose as `omn_cl_typ`. Tokens can be bound to a value,
+/// going stale when it changes, and consumed once through a `ConsumedTokenStore`.
+pub struct PurposeTokens<St> {
+    key: Arc<[u8]>,
+    consumed: Arc<St>,
+    clock: Arc<dyn Clock>,
+    claims_validation: ClaimsValidation,
+}
+
+impl<St> Clone for PurposeTokens<St> {
+    fn clone(&self) -> Self {
+        PurposeTokens {
+            key: self.key.clone(),
+            consumed: self.consumed.clone(),
+            clock: self.clock.clone(),
+            claims_validation: self.claims_validation.clone(),
+        }
+    }
+}
+
+impl<St> PurposeTokens<St>
+where
+    St: ConsumedTokenStore,
+{
+    /// Tokens signed with the key, which should differ from the key signing sessions.
+    pub fn new(key: &[u8], consumed: St) -> PurposeTokens<St> {
+        PurposeTokens {
+            key: Arc::from(key),
+            consumed: Arc::new(consumed),
+            clock: Arc::new(SystemClock),
+            claims_validation: ClaimsValidation::new(),
+        }
+    }
+
+    /// Issues and verifies tokens by the clock, instead of `SystemClock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_claims_validation(mut self, claims_validation: ClaimsValidation) -> Self {
+        self.claims_validation = claims_validation;
+        self
+    }
+
+    pub fn consumed(&self) -> &St {
+        &self.consumed
+    }
+
+    /// Issues a token for the purpose, valid for its ttl, optionally bound to a value such as the
+    /// account's current password hash or email.
+    pub fn issue(
+        &self,
+        purpose: &TokenPurpose,
+        account_id: &str,
+        binding: Option<&str>,
+    ) -> anyhow::Result<String> {
+        self.issue_with_claims(purpose, account_id, binding, ())
+    }
+
+    /// Issues a token for the purpose with custom claims, such as the org of an invitation.
+    pub fn issue_with_claims<C: Serialize>(
+        &self,
+        purpose: &TokenPurpose,
+        account_id: &str,
+        binding: Option<&str>,
+        custom: C,
+    ) -> anyhow::Result<String> {
+        if purpose.is_reserved() {
+            anyhow::bail!(
+                "Reserved claims type for a token purpose: {}",
+                purpose.claims_type
+            );
+        }
+
+        let claims = SessionClaims {
+            omn_cl_typ: purpose.claims_type.into(),
+            ..SessionClaims::new_at(
+                self.clock.as_ref(),
+                account_id,
+                purpose.ttl,
+                PurposeClaims {
+                    bnd: binding
+                        .map(|binding| binding_hash(&self.key, binding))
+                        .transpose()?,
+                    custom,
+                },
+            )?
+        };
+        encode_hs256(&claims, &self.key)
+    }
+
+    /// Verifies a token for the purpose, and against the current value it was bound to, without
+    /// consuming it. Such as to render a password reset form before it is submitted.
+    pub fn verify(
+        &self,
+        purpose: &TokenPurpose,
+        token: &str,
+        binding: Option<&str>,
+    ) -> Result<SessionClaims<PurposeClaims>, PurposeTokenError> {
+        self.verify_with_claims(purpose, token, binding)
+    }
+
+    pub fn verify_with_claims<C: DeserializeOwned>(
+        &self,
+        purpose: &TokenPurpose,
+        token: &str,
+        binding: Option<&str>,
+    ) -> Result<SessionClaims<PurposeClaims<C>>, PurposeTokenError> {
+        let claims: SessionClaims<PurposeClaims<C>> =
+            decode_hs256(token, &self.key).map_err(|_| PurposeTokenError::Invalid)?;
+
+        if claims.jti.is_none() {
+            return Err(PurposeTokenError::Invalid);
+        }
+        if claims.omn_cl_typ != purpose.claims_type {
+            return Err(PurposeTokenError::WrongPurpose);
+        }
+        self.claims_validation
+            .validate_at(&claims, self.clock.now_secs())
+            .map_err(PurposeTokenError::Claims)?;
+        let expected = binding
+            .map(|binding| binding_hash(&self.key, binding))
+            .transpose()
+            .map_err(|_| PurposeTokenError::Invalid)?;
+        if claims.custom.bnd != expected {
+            return Err(PurposeTokenError::Stale);
+        }
+
+        Ok(claims)
+    }
+
+    /// Verifies a token for the purpose and consumes it, rejecting it on any later use. Rejections
+    /// are `PurposeTokenError`s, while other errors come from the `ConsumedTokenStore`.
+    pub async fn consume(
+        &self,
+        purpose: &TokenPurpose,
+        token: &str,
+        binding: Option<&str>,
+    ) -> anyhow::Result<SessionClaims<PurposeClaims>> {
+        self.consume_with_claims(purpose, token, binding).await
+    }
+
+    pub async fn consume_with_claims<C: DeserializeOwned>(
+        &self,
+        purpose: &TokenPurpose,
+        token: &str,
+        binding: Option<&str>,
+    ) -> anyhow::Result<SessionClaims<PurposeClaims<C>>> {
+        let claims = self.verify_with_claims(purpose, token, binding)?;
+
+        // Tokens stay valid for the leeway past their expiry, and so must stay consumed.
+        let jti = claims.jti.as_deref().unwrap_or_default();
+        let expires_at = claims.exp as u64 + self.claims_validation.leeway().as_secs();
+        if !self.consumed.consume(jti, expires_at).await? {
+            return Err(PurposeTokenError::AlreadyUsed.into());
+        }
+
+        Ok(claims)
+    }
+}
@@ -0,0 +1,269 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::Mac;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::session::clock::MockClock;
+use crate::session::memory::InMemorySessionManager;
+use crate::session::purpose::{
+    ConsumedTokenStore, MemoryConsumedTokenStore, PurposeTokenError, PurposeTokens, TokenPurpose,
+};
+use crate::session::session::{authorize, resolve, Credential, SESSION_CLAIMS_TYPE};
+use crate::session::token::sign;
+use crate::session::validation::ClaimsError;
+use crate::testing::client::TestClient;
+
+const KEY: &[u8] = b"test-signing-key";
+
+const NOW: u64 = 1_700_000_000;
+
+fn clock() -> MockClock {
+    MockClock::at(UNIX_EPOCH + Duration::from_secs(NOW))
+}
+
+fn tokens(clock: &MockClock) -> PurposeTokens<MemoryConsumedTokenStore> {
+    PurposeTokens::new(
+        KEY,
+        MemoryConsumedTokenStore::new().with_clock(clock.clone()),
+    )
+    .with_clock(clock.clone())
+}
+
+fn rejection(err: anyhow::Error) -> PurposeTokenError {
+    *err.downcast_ref::<PurposeTokenError>().unwrap()
+}
+
+#[tokio::test]
+async fn test_token_is_consumed_once() {
+    let tokens = tokens(&clock());
+    let token = tokens
+        .issue(&TokenPurpose::EMAIL_VERIFICATION, "test-account-id", None)
+        .unwrap();
+
+    let claims = tokens
+        .verify(&TokenPurpose::EMAIL_VERIFICATION, &token, None)
+        .unwrap();
+    assert_eq!(claims.sub, "test-account-id");
+    assert_eq!(claims.omn_cl_typ, "email_verification");
+    assert_eq!(claims.exp, (NOW + 24 * 60 * 60) as usize);
+
+    tokens
+        .consume(&TokenPurpose::EMAIL_VERIFICATION, &token, None)
+        .await
+        .unwrap();
+    let err = tokens
+        .consume(&TokenPurpose::EMAIL_VERIFICATION, &token, None)
+        .await
+        .unwrap_err();
+    assert_eq!(rejection(err), PurposeTokenError::AlreadyUsed);
+}
+
+#[tokio::test]
+async fn test_token_expires_after_its_purpose_ttl() {
+    let clock = clock();
+    let tokens = tokens(&clock);
+    let magic_link = tokens
+        .issue(&TokenPurpose::MAGIC_LINK, "test-account-id", None)
+        .unwrap();
+    let short = TokenPurpose::new("short", Duration::from_secs(60));
+    let short_lived = tokens.issue(&short, "test-account-id", None).unwrap();
+
+    clock.advance(Duration::from_secs(121));
+    assert_eq!(
+        tokens.verify(&short, &short_lived, None).unwrap_err(),
+        PurposeTokenError::Claims(ClaimsError::Expired)
+    );
+    assert!(tokens
+        .verify(&TokenPurpose::MAGIC_LINK, &magic_link, None)
+        .is_ok());
+
+    clock.advance(Duration::from_secs(15 * 60));
+    assert_eq!(
+        tokens
+            .verify(&TokenPurpose::MAGIC_LINK, &magic_link, None)
+            .unwrap_err(),
+        PurposeTokenError::Claims(ClaimsError::Expired)
+    );
+}
+
+#[tokio::test]
+async fn test_bound_token_goes_stale_when_the_value_changes() {
+    let tokens = tokens(&clock());
+    let token = tokens
+        .issue(
+            &TokenPurpose::PASSWORD_RESET,
+            "test-account-id",
+            Some("current-password-hash"),
+        )
+        .unwrap();
+
+    assert!(!token.contains("current-password-hash"));
+    assert!(tokens
+        .verify(
+            &TokenPurpose::PASSWORD_RESET,
+            &token,
+            Some("current-password-hash")
+        )
+        .is_ok());
+
+    for binding in [Some("new-password-hash"), None] {
+        assert_eq!(
+            tokens
+                .verify(&TokenPurpose::PASSWORD_RESET, &token, binding)
+                .unwrap_err(),
+            PurposeTokenError::Stale
+        );
+    }
+
+    let err = tokens
+        .consume(
+            &TokenPurpose::PASSWORD_RESET,
+            &token,
+            Some("new-password-hash"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(rejection(err), PurposeTokenError::Stale);
+}
+
+#[test]
+fn test_binding_hash_is_keyed() {
+    let clock = clock();
+    let other = PurposeTokens::new(b"other-signing-key", MemoryConsumedTokenStore::new())
+        .with_clock(clock.clone());
+    let binding = Some("test@example.com");
+
+    let bnd = |tokens: &PurposeTokens<MemoryConsumedTokenStore>| {
+        let token = tokens
+            .issue(
+                &TokenPurpose::EMAIL_VERIFICATION,
+                "test-account-id",
+                binding,
+            )
+            .unwrap();
+        tokens
+            .verify(&TokenPurpose::EMAIL_VERIFICATION, &token, binding)
+            .unwrap()
+            .custom
+            .bnd
+            .unwrap()
+    };
+
+    let hash = bnd(&tokens(&clock));
+    assert_ne!(
+        hash,
+        URL_SAFE_NO_PAD.encode(Sha256::digest("test@example.com"))
+    );
+    assert_ne!(
+        hash,
+        URL_SAFE_NO_PAD.encode(
+            sign(KEY, "test@example.com")
+                .unwrap()
+                .finalize()
+                .into_bytes()
+        )
+    );
+    assert_ne!(hash, bnd(&other));
+}
+
+#[tokio::test]
+async fn test_token_is_rejected_for_other_purposes_and_sessions() {
+    let tokens = tokens(&clock());
+    let token = tokens
+        .issue(&TokenPurpose::PASSWORD_RESET, "test-account-id", None)
+        .unwrap();
+
+    assert_eq!(
+        tokens
+            .verify(&TokenPurpose::MAGIC_LINK, &token, None)
+            .unwrap_err(),
+        PurposeTokenError::WrongPurpose
+    );
+    assert_eq!(
+        tokens
+            .verify(&TokenPurpose::PASSWORD_RESET, "not-a-token", None)
+            .unwrap_err(),
+        PurposeTokenError::Invalid
+    );
+    assert!(tokens
+        .issue(
+            &TokenPurpose::new(SESSION_CLAIMS_TYPE, Duration::from_secs(60)),
+            "test-account-id",
+            None
+        )
+        .is_err());
+
+    #[derive(Clone)]
+    struct FakeAccount;
+
+    let sessions = InMemorySessionManager::new(KEY);
+    sessions.insert_account("test-account-id", FakeAccount);
+    let client = TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(_): Extension<FakeAccount>| async {}),
+            )
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                authorize::<FakeAccount, InMemorySessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                resolve::<FakeAccount, InMemorySessionManager<FakeAccount>>,
+            ))
+            .with_state(sessions),
+    );
+
+    client
+        .get("/api/account")
+        .bearer(&Credential(token))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_token_carries_custom_claims() {
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct InvitationClaims {
+        org_id: String,
+    }
+
+    let tokens = tokens(&clock());
+    let token = tokens
+        .issue_with_claims(
+            &TokenPurpose::INVITATION,
+            "invitee@example.com",
+            None,
+            InvitationClaims {
+                org_id: "test-org-id".into(),
+            },
+        )
+        .unwrap();
+
+    let claims = tokens
+        .consume_with_claims::<InvitationClaims>(&TokenPurpose::INVITATION, &token, None)
+        .await
+        .unwrap();
+    assert_eq!(claims.sub, "invitee@example.com");
+    assert_eq!(claims.custom.custom.org_id, "test-org-id");
+}
+
+#[tokio::test]
+async fn test_memory_store_prunes_expired_tokens() {
+    let clock = clock();
+    let store = MemoryConsumedTokenStore::new().with_clock(clock.clone());
+
+    assert!(store.consume("test-jti", NOW + 60).await.unwrap());
+    assert!(!store.consume("test-jti", NOW + 60).await.unwrap());
+
+    clock.advance(Duration::from_secs(61));
+    assert!(store.consume("other-jti", NOW + 120).await.unwrap());
+    assert!(store.consume("test-jti", NOW + 120).await.unwrap());
+}
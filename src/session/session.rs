@@ -1,55 +1,406 @@
-use std::{
-    ops::Add,
-    time::{Duration, SystemTime},
-};
+use std::{ops::Add, time::Duration};
 
 use async_trait::async_trait;
-use axum::extract::{MatchedPath, State};
+use axum::extract::{FromRequestParts, MatchedPath, State};
+use axum::http::{request::Parts, Extensions};
 use axum_extra::extract::CookieJar;
 
 use axum::{extract::Request, http::StatusCode, middleware::Next};
-use log::info;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::api::response::{JsonResponse, ResponseError};
+use crate::api::response::{JsonResponse, JsonStatus, ResponseError};
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::impersonation::{
+    Actor, ImpersonationAudit, Impersonator, MAX_IMPERSONATION_LIFETIME,
+};
+use crate::session::principal::{
+    AnonymousId, Principal, PrincipalKind, SERVICE_REQUIRED_DETAIL, USER_REQUIRED_DETAIL,
+};
+use crate::session::tenant::Tenant;
+use crate::session::validation::{Audience, ClaimsValidation};
+use crate::telemetry::{self, SessionSpan};
 
 pub const SESSION_CLAIMS_TYPE: &str = "session";
 
+pub const SERVICE_CLAIMS_TYPE: &str = "service";
+
+pub const SOCKET_TICKET_CLAIMS_TYPE: &str = "socket_ticket";
+
+/// The default cap on the lifetime of a socket ticket, from its `iat` to its `exp`. Tickets travel
+/// in the query string, so are kept short-lived.
+pub const MAX_SOCKET_TICKET_LIFETIME: Duration = Duration::from_secs(60);
+
+pub const ACCOUNT_SUSPENDED_DETAIL: &str = "Account suspended.";
+
+pub const VERIFICATION_REQUIRED_DETAIL: &str = "Account verification required.";
+
+/// The standing of a resolved account, inserted into the request extensions by `resolve`.
+///
+/// Requests from suspended accounts are rejected by `resolve` with a 403 response. Requests from
+/// unverified accounts are rejected by `authorize` with a 403 response, unless the route is allowed
+/// by `SessionManager::allows_unverified`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountStatus {
+    Active,
+    Unverified,
+    Suspended,
+}
+
+/// Marks a request from an unverified account to a route allowed by
+/// `SessionManager::allows_unverified`.
+#[derive(Clone, Copy, Debug)]
+struct UnverifiedAllowed;
+
+/// Whether the account resolved for the request is unverified, on a route that doesn't allow it.
+pub(crate) fn verification_required(extensions: &Extensions) -> bool {
+    extensions.get::<AccountStatus>() == Some(&AccountStatus::Unverified)
+        && extensions.get::<UnverifiedAllowed>().is_none()
+}
+
 #[async_trait]
-pub trait SessionManager<U> {
-    async fn decode_claims(&self, token: Credential) -> anyhow::Result<SessionClaims>;
+pub trait SessionManager<U, C = ()>: Send + Sync {
+    async fn decode_claims(&self, token: Credential) -> anyhow::Result<SessionClaims<C>>;
 
     async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>>;
 
+    /// Looks up the account with its status. Defaults to `get_account`, treating every account as
+    /// active.
+    async fn get_account_status(
+        &self,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        Ok(self
+            .get_account(account_id)
+            .await?
+            .map(|account| (account, AccountStatus::Active)))
+    }
+
+    /// Looks up the account within the request's tenant, if any. Defaults to `get_account_status`,
+    /// ignoring the tenant.
+    async fn get_tenant_account(
+        &self,
+        _tenant: Option<&Tenant>,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        self.get_account_status(account_id).await
+    }
+
     fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential>;
+
+    /// Extracts the credentials `resolve` decodes with `decode_credentials`, each with the index of
+    /// the source it came from. Defaults to the credential of `extract_credential`, from source 0.
+    /// Override both, as `CredentialChain` does, to accept several credentials on a request.
+    fn extract_credentials(
+        &self,
+        request: &Request,
+        cookies: &CookieJar,
+    ) -> Vec<(usize, Credential)> {
+        self.extract_credential(request, cookies)
+            .map(|credential| (0, credential))
+            .into_iter()
+            .collect()
+    }
+
+    /// Decodes the claims of the credentials of a request, as extracted by `extract_credentials`.
+    /// Defaults to `decode_claims` of the first credential.
+    async fn decode_credentials(
+        &self,
+        credentials: Vec<(usize, Credential)>,
+    ) -> anyhow::Result<SessionClaims<C>> {
+        match credentials.into_iter().next() {
+            Some((_, credential)) => self.decode_claims(credential).await,
+            None => anyhow::bail!("No credential"),
+        }
+    }
+
+    /// Resolves the tenant of the request, such as with a `TenantSource`. Defaults to none, for
+    /// single-tenant applications.
+    fn resolve_tenant(&self, _request: &Request) -> Option<Tenant> {
+        None
+    }
+
+    /// Called on every request with an impersonated session, after both accounts are resolved and
+    /// `resolve` has logged the request. Failing to audit fails the request. Defaults to doing
+    /// nothing.
+    async fn audit_impersonation(&self, _audit: &ImpersonationAudit) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// The longest lifetime `resolve` accepts for an impersonated session, from its `iat` to its
+    /// `exp`. Defaults to `MAX_IMPERSONATION_LIFETIME`.
+    fn max_impersonation_lifetime(&self) -> Duration {
+        MAX_IMPERSONATION_LIFETIME
+    }
+
+    /// The longest lifetime `SocketCaller` accepts for a socket ticket, from its `iat` to its
+    /// `exp`. Defaults to `MAX_SOCKET_TICKET_LIFETIME`.
+    fn max_socket_ticket_lifetime(&self) -> Duration {
+        MAX_SOCKET_TICKET_LIFETIME
+    }
+
+    /// Identifies anonymous callers, such as for rate limiting, with `AnonymousId::of` keyed with
+    /// an application secret, or `AnonymousId::of_client` from a trusted proxy header. Defaults to
+    /// none, leaving anonymous callers unidentified.
+    fn anonymous_id(&self, _request: &Request) -> Option<AnonymousId> {
+        None
+    }
+
+    /// The validation of registered claims, such as the expected issuer and audience, applied by
+    /// `resolve` to every decoded session. Defaults to `ClaimsValidation::new()`.
+    fn claims_validation(&self) -> &ClaimsValidation {
+        ClaimsValidation::default_ref()
+    }
+
+    /// The clock `resolve` validates claims against. Defaults to `SystemClock`.
+    fn clock(&self) -> &dyn Clock {
+        &SystemClock
+    }
+
+    /// Whether unverified accounts may access the request's route, such as the route verifying an
+    /// email address. Defaults to none.
+    fn allows_unverified(&self, _request: &Request) -> bool {
+        false
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SessionClaims {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionClaims<C = ()> {
     pub sub: String,
     pub exp: usize,
     pub omn_cl_typ: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<usize>,
+    /// A unique id for the claims.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// The tenant the session was issued for, which must match the tenant of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// The real actor of an impersonated session, while `sub` is the impersonated account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// Application claims, such as a display name or an org id, flattened into the claims.
+    /// Available to handlers as `CustomClaims<C>`.
+    #[serde(flatten)]
+    pub custom: C,
+}
+
+impl<C> SessionClaims<C> {
+    /// Claims for the account, issued now with a unique `jti`.
+    pub fn new(
+        account_id: &str,
+        expires_in: Duration,
+        custom: C,
+    ) -> anyhow::Result<SessionClaims<C>> {
+        SessionClaims::new_at(&SystemClock, account_id, expires_in, custom)
+    }
+
+    /// Claims for the account, issued at the current time of the clock.
+    pub fn new_at(
+        clock: &dyn Clock,
+        account_id: &str,
+        expires_in: Duration,
+        custom: C,
+    ) -> anyhow::Result<SessionClaims<C>> {
+        Ok(SessionClaims {
+            sub: String::from(account_id),
+            exp: SessionClaims::expires_in_at(clock, expires_in)?,
+            omn_cl_typ: SESSION_CLAIMS_TYPE.into(),
+            iss: None,
+            aud: None,
+            iat: Some(SessionClaims::expires_in_at(clock, Duration::ZERO)?),
+            nbf: None,
+            jti: Some(Uuid::new_v4().to_string()),
+            tenant: None,
+            act: None,
+            custom,
+        })
+    }
+
+    /// Replaces the custom claims, such as to add them to service or impersonation claims.
+    pub fn with_custom<D>(self, custom: D) -> SessionClaims<D> {
+        SessionClaims {
+            sub: self.sub,
+            exp: self.exp,
+            omn_cl_typ: self.omn_cl_typ,
+            iss: self.iss,
+            aud: self.aud,
+            iat: self.iat,
+            nbf: self.nbf,
+            jti: self.jti,
+            tenant: self.tenant,
+            act: self.act,
+            custom,
+        }
+    }
+
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.iss = Some(issuer.into());
+        self
+    }
+
+    pub fn with_audience(mut self, audience: Audience) -> Self {
+        self.aud = Some(audience);
+        self
+    }
+
+    /// Sets the claims to be valid only from `not_before_in` after they were issued, or from now
+    /// without an `iat`.
+    pub fn with_not_before(self, not_before_in: Duration) -> anyhow::Result<Self> {
+        self.with_not_before_at(&SystemClock, not_before_in)
+    }
+
+    /// Like `with_not_before`, from the current time of the clock without an `iat`.
+    pub fn with_not_before_at(
+        mut self,
+        clock: &dyn Clock,
+        not_before_in: Duration,
+    ) -> anyhow::Result<Self> {
+        let not_before = match self.iat {
+            Some(iat) => iat.saturating_add(usize::try_from(not_before_in.as_secs())?),
+            None => SessionClaims::expires_in_at(clock, not_before_in)?,
+        };
+        self.nbf = Some(not_before);
+        Ok(self)
+    }
+
+    /// Sets the tenant the claims are issued for, lowercased like tenants resolved from requests.
+    pub fn with_tenant(mut self, tenant: &Tenant) -> Self {
+        self.tenant = Some(tenant.0.to_ascii_lowercase());
+        self
+    }
+
+    /// Whether the claims were issued for the tenant, or both are without a tenant.
+    pub fn matches_tenant(&self, tenant: Option<&Tenant>) -> bool {
+        self.tenant.as_deref() == tenant.map(Tenant::as_str)
+    }
 }
 
 impl SessionClaims {
     pub fn expires_in(duration: Duration) -> anyhow::Result<usize> {
+        SessionClaims::expires_in_at(&SystemClock, duration)
+    }
+
+    /// The expiry `duration` from the current time of the clock, in seconds since the Unix epoch.
+    pub fn expires_in_at(clock: &dyn Clock, duration: Duration) -> anyhow::Result<usize> {
         Ok(usize::try_from(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)?
+            clock
+                .now()
+                .duration_since(std::time::UNIX_EPOCH)?
                 .add(duration)
                 .as_secs(),
         )?)
     }
 
-    pub fn new(account_id: &str, expires_in: Duration) -> anyhow::Result<SessionClaims> {
+    /// Claims for the actor to act as the account, such as for support staff seeing the app as a
+    /// given user. The lifetime is capped at `MAX_IMPERSONATION_LIFETIME`.
+    pub fn new_impersonation(
+        actor_id: &str,
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<SessionClaims> {
+        SessionClaims::new_impersonation_at(&SystemClock, actor_id, account_id, expires_in)
+    }
+
+    /// Impersonation claims issued at the current time of the clock.
+    pub fn new_impersonation_at(
+        clock: &dyn Clock,
+        actor_id: &str,
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<SessionClaims> {
         Ok(SessionClaims {
-            sub: String::from(account_id),
-            exp: SessionClaims::expires_in(expires_in)?,
-            omn_cl_typ: SESSION_CLAIMS_TYPE.into(),
+            act: Some(Actor {
+                sub: String::from(actor_id),
+            }),
+            ..SessionClaims::new_at(
+                clock,
+                account_id,
+                expires_in.min(MAX_IMPERSONATION_LIFETIME),
+                (),
+            )?
+        })
+    }
+
+    /// Claims for a service account, resolved as `Principal::Service`.
+    pub fn new_service(account_id: &str, expires_in: Duration) -> anyhow::Result<SessionClaims> {
+        SessionClaims::new_service_at(&SystemClock, account_id, expires_in)
+    }
+
+    /// Service claims issued at the current time of the clock.
+    pub fn new_service_at(
+        clock: &dyn Clock,
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<SessionClaims> {
+        Ok(SessionClaims {
+            omn_cl_typ: SERVICE_CLAIMS_TYPE.into(),
+            ..SessionClaims::new_at(clock, account_id, expires_in, ())?
+        })
+    }
+
+    /// Claims for a short-lived ticket authenticating a WebSocket upgrade, for browser clients that
+    /// can't set headers on WebSocket connections. Tickets are rejected by `resolve`. The lifetime
+    /// is capped at `MAX_SOCKET_TICKET_LIFETIME`.
+    pub fn new_socket_ticket(
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<SessionClaims> {
+        SessionClaims::new_socket_ticket_at(&SystemClock, account_id, expires_in)
+    }
+
+    /// Socket ticket claims issued at the current time of the clock.
+    pub fn new_socket_ticket_at(
+        clock: &dyn Clock,
+        account_id: &str,
+        expires_in: Duration,
+    ) -> anyhow::Result<SessionClaims> {
+        Ok(SessionClaims {
+            omn_cl_typ: SOCKET_TICKET_CLAIMS_TYPE.into(),
+            ..SessionClaims::new_at(
+                clock,
+                account_id,
+                expires_in.min(MAX_SOCKET_TICKET_LIFETIME),
+                (),
+            )?
         })
     }
 }
 
+/// The custom claims of the resolved session, inserted into the request extensions by
+/// `resolve_with_claims`.
+#[derive(Clone, Debug)]
+pub struct CustomClaims<C>(pub C);
+
+impl<C, S> FromRequestParts<S> for CustomClaims<C>
+where
+    C: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = JsonResponse<JsonStatus>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<CustomClaims<C>>()
+            .cloned()
+            .ok_or_else(|| JsonResponse::of_status(StatusCode::UNAUTHORIZED))
+    }
+}
+
 #[derive(Clone)]
 pub struct Credential(pub String);
 
@@ -66,64 +417,294 @@ impl Credential {
     pub fn from_cookie(cookie_name: &str, cookies: &CookieJar) -> Option<Credential> {
         cookies
             .get(cookie_name)
-            .and_then(|cookie| Some(cookie.value_trimmed()))
+            .map(|cookie| cookie.value_trimmed())
             .map(|header| Credential(header.into()))
     }
 }
 
+/// The route path matched by the router, such as `/api/users/{id}`.
+pub fn matched_path(request: &Request) -> Option<&str> {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+}
+
 pub async fn authorize<U: Clone + Send + Sync + 'static, S: SessionManager<U>>(
     request: Request,
     next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    authorize_request::<U>(request, next, None).await
+}
+
+/// Like `authorize`, also rejecting service callers with a 403 response.
+pub async fn authorize_user<U: Clone + Send + Sync + 'static, S: SessionManager<U>>(
+    request: Request,
+    next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    authorize_request::<U>(request, next, Some(PrincipalKind::User)).await
+}
+
+/// Like `authorize`, also rejecting user callers with a 403 response.
+pub async fn authorize_service<U: Clone + Send + Sync + 'static, S: SessionManager<U>>(
+    request: Request,
+    next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    authorize_request::<U>(request, next, Some(PrincipalKind::Service)).await
+}
+
+async fn authorize_request<U: Clone + Send + Sync + 'static>(
+    request: Request,
+    next: Next,
+    kind: Option<PrincipalKind>,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    let span = SessionSpan::authorize(matched_path(&request));
+
+    span.clone()
+        .instrument(authorize_inner::<U>(span, request, next, kind))
+        .await
+}
+
+async fn authorize_inner<U: Clone + Send + Sync + 'static>(
+    span: SessionSpan,
+    request: Request,
+    next: Next,
+    kind: Option<PrincipalKind>,
 ) -> core::result::Result<axum::response::Response, ResponseError> {
     if request.extensions().get::<U>().is_some() {
+        if verification_required(request.extensions()) {
+            span.record_outcome("verification_required");
+            telemetry::session_event(format_args!(
+                "Forbidden! Account verification was required."
+            ));
+            return Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                .with_detail(VERIFICATION_REQUIRED_DETAIL)
+                .into());
+        }
+
+        if let Some(kind) = kind {
+            let principal = request.extensions().get::<Principal<U>>();
+            if principal.and_then(Principal::kind) != Some(kind) {
+                span.record_outcome("wrong_principal");
+                telemetry::session_event(format_args!(
+                    "Forbidden! A {:?} caller was required.",
+                    kind
+                ));
+                return Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                    .with_detail(match kind {
+                        PrincipalKind::User => USER_REQUIRED_DETAIL,
+                        PrincipalKind::Service => SERVICE_REQUIRED_DETAIL,
+                    })
+                    .into());
+            }
+        }
+
+        span.record_outcome("authorized");
         Ok(next.run(request).await)
     } else {
-        info!("Unauthorized! Authentication was required.");
+        span.record_outcome("unauthorized");
+        telemetry::session_event(format_args!("Unauthorized! Authentication was required."));
         Err(JsonResponse::of_status(StatusCode::UNAUTHORIZED).into())
     }
 }
 
 pub async fn resolve<U: Clone + Send + Sync + 'static, S: SessionManager<U>>(
+    state: State<S>,
+    cookies: CookieJar,
+    request: Request,
+    next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    resolve_with_claims::<U, (), S>(state, cookies, request, next).await
+}
+
+/// Like `resolve`, for a `SessionManager` decoding custom claims, which are inserted into the
+/// request extensions as `CustomClaims<C>`.
+pub async fn resolve_with_claims<
+    U: Clone + Send + Sync + 'static,
+    C: Clone + Send + Sync + 'static,
+    S: SessionManager<U, C>,
+>(
     State(session_manager): State<S>,
     cookies: CookieJar,
+    request: Request,
+    next: Next,
+) -> core::result::Result<axum::response::Response, ResponseError> {
+    let span = SessionSpan::resolve(matched_path(&request));
+
+    span.clone()
+        .instrument(resolve_request::<U, C, S>(
+            span,
+            session_manager,
+            cookies,
+            request,
+            next,
+        ))
+        .await
+}
+
+async fn resolve_request<
+    U: Clone + Send + Sync + 'static,
+    C: Clone + Send + Sync + 'static,
+    S: SessionManager<U, C>,
+>(
+    span: SessionSpan,
+    session_manager: S,
+    cookies: CookieJar,
     mut request: Request,
     next: Next,
 ) -> core::result::Result<axum::response::Response, ResponseError> {
-    let path = request.extensions().get::<MatchedPath>();
-    match path {
-        Some(path) => info!("Authenticating path: {}", path.as_str()),
-        None => info!("Authenticating path: {}", "No matched path"),
+    telemetry::session_event(format_args!(
+        "Authenticating path: {}",
+        matched_path(&request).unwrap_or("No matched path")
+    ));
+
+    let anonymous = Principal::<U>::Anonymous(session_manager.anonymous_id(&request));
+    request.extensions_mut().insert(anonymous);
+
+    let tenant = session_manager.resolve_tenant(&request);
+    if let Some(tenant) = &tenant {
+        request.extensions_mut().insert(tenant.clone());
     }
 
-    let credential = session_manager.extract_credential(&request, &cookies);
+    let credentials = session_manager.extract_credentials(&request, &cookies);
 
-    if let Some(credential) = credential {
-        if let Ok(decoded) = session_manager.decode_claims(credential).await {
-            if decoded.omn_cl_typ != SESSION_CLAIMS_TYPE {
-                info!("Account resolve failed! Illegal claims type.");
+    if !credentials.is_empty() {
+        if let Ok(decoded) = session_manager.decode_credentials(credentials).await {
+            let now = session_manager.clock().now_secs();
+
+            if let Err(err) = session_manager
+                .claims_validation()
+                .validate_at(&decoded, now)
+            {
+                span.record_outcome(err.outcome());
+                telemetry::session_event(format_args!("Account resolve failed! {}.", err));
                 return Ok(next.run(request).await);
             }
 
+            let kind = match decoded.omn_cl_typ.as_str() {
+                SESSION_CLAIMS_TYPE => Some(PrincipalKind::User),
+                SERVICE_CLAIMS_TYPE if decoded.act.is_none() => Some(PrincipalKind::Service),
+                _ => None,
+            };
+
+            let Some(kind) = kind else {
+                span.record_outcome("illegal_claims_type");
+                telemetry::session_event(format_args!(
+                    "Account resolve failed! Illegal claims type."
+                ));
+                return Ok(next.run(request).await);
+            };
+
+            if !decoded.matches_tenant(tenant.as_ref()) {
+                span.record_outcome("tenant_mismatch");
+                telemetry::session_event(format_args!(
+                    "Account resolve failed! Claims were issued for another tenant."
+                ));
+                return Ok(next.run(request).await);
+            }
+
+            let impersonator = match &decoded.act {
+                Some(actor) => {
+                    let lifetime = decoded
+                        .iat
+                        .map(|iat| decoded.exp.saturating_sub(iat) as u64);
+                    let max_lifetime = session_manager.max_impersonation_lifetime().as_secs();
+                    if lifetime.is_none_or(|lifetime| lifetime > max_lifetime) {
+                        span.record_outcome("impersonation_lifetime_exceeded");
+                        telemetry::session_event(format_args!("Account resolve failed! Impersonated session has no issue time or outlives the cap."));
+                        return Ok(next.run(request).await);
+                    }
+
+                    match session_manager
+                        .get_tenant_account(tenant.as_ref(), actor.sub.clone())
+                        .await?
+                    {
+                        Some((account, AccountStatus::Active)) => Some((actor.clone(), account)),
+                        _ => {
+                            span.record_outcome("actor_not_found");
+                            telemetry::session_event(format_args!("Account resolve failed! Impersonating actor is not an active account."));
+                            return Ok(next.run(request).await);
+                        }
+                    }
+                }
+                None => None,
+            };
+
             let account_id = decoded.sub;
 
-            let lookup = session_manager.get_account(account_id).await?;
+            let lookup = session_manager
+                .get_tenant_account(tenant.as_ref(), account_id.clone())
+                .await?;
+            if lookup.is_some() {
+                span.record_account_id(&account_id);
+            }
 
             match lookup {
-                Some(account) => {
+                Some((_, AccountStatus::Suspended)) => {
+                    span.record_outcome("account_suspended");
+                    telemetry::session_event(format_args!(
+                        "Account resolve failed! Account is suspended."
+                    ));
+                    return Err(JsonResponse::of_status(StatusCode::FORBIDDEN)
+                        .with_detail(ACCOUNT_SUSPENDED_DETAIL)
+                        .into());
+                }
+                Some((account, status)) => {
+                    if status == AccountStatus::Unverified
+                        && session_manager.allows_unverified(&request)
+                    {
+                        request.extensions_mut().insert(UnverifiedAllowed);
+                    }
+                    if let Some((actor, actor_account)) = impersonator {
+                        let audit =
+                            ImpersonationAudit::of(&actor, &account_id, tenant.as_ref(), &request);
+                        telemetry::session_event(format_args!(
+                            "Impersonated request by {} as {}: {} {}",
+                            audit.actor_id, audit.account_id, audit.method, audit.path
+                        ));
+                        session_manager.audit_impersonation(&audit).await?;
+                        request.extensions_mut().insert(actor);
+                        request
+                            .extensions_mut()
+                            .insert(Impersonator::<U>(actor_account));
+                    }
+                    request.extensions_mut().insert(match kind {
+                        PrincipalKind::User => Principal::User(account.clone()),
+                        PrincipalKind::Service => Principal::Service(account.clone()),
+                    });
                     request.extensions_mut().insert::<U>(account);
-                    info!("Inserted account to request extensions...");
+                    request.extensions_mut().insert(status);
+                    request
+                        .extensions_mut()
+                        .insert(CustomClaims(decoded.custom));
+                    span.record_outcome(match status {
+                        AccountStatus::Unverified => "resolved_unverified",
+                        _ => "resolved",
+                    });
+                    telemetry::session_event(format_args!(
+                        "Inserted account to request extensions..."
+                    ));
                 }
                 None => {
-                    info!("Account resolve failed! Account lookup returned no result.");
+                    span.record_outcome("account_not_found");
+                    telemetry::session_event(format_args!(
+                        "Account resolve failed! Account lookup returned no result."
+                    ));
                     return Ok(next.run(request).await);
                 }
             }
         } else {
-            info!("Account resolve failed! Unable to decode claims.");
+            span.record_outcome("invalid_claims");
+            telemetry::session_event(format_args!(
+                "Account resolve failed! Unable to decode claims."
+            ));
             return Ok(next.run(request).await);
         }
     } else {
-        info!("Account resolve skipped: No credential in request.");
+        span.record_outcome("no_credential");
+        telemetry::session_event(format_args!(
+            "Account resolve skipped: No credential in request."
+        ));
         return Ok(next.run(request).await);
     }
 
@@ -13,7 +13,12 @@ use http_body_util::BodyExt;
 use tower::ServiceExt;
 
 use crate::api::response::JsonStatus;
-use crate::session::session::{authorize, resolve, Credential, SessionClaims, SessionManager};
+use crate::session::session::{
+    authorize, resolve, resolve_with_claims, AccountStatus, Credential, CustomClaims,
+    SessionClaims, SessionManager, ACCOUNT_SUSPENDED_DETAIL, VERIFICATION_REQUIRED_DETAIL,
+};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
 
 #[derive(Clone)]
 struct FakeAccount {
@@ -78,7 +83,7 @@ fn app(state: Arc<FakeAppState>) -> Router {
 async fn test_session_header_is_accepted() {
     let state = fake_app_state();
     let claims = fake_encode_claims(
-        &SessionClaims::new("test-account-id", Duration::from_secs(60)).unwrap(),
+        &SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap(),
     );
 
     let app = app(state).into_service();
@@ -109,7 +114,7 @@ async fn test_missing_header_bearer_prefix_is_rejected() {
     let state = fake_app_state();
 
     let claims = fake_encode_claims(
-        &SessionClaims::new("test-account-id", Duration::from_secs(60)).unwrap(),
+        &SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap(),
     );
 
     let app = app(state).into_service();
@@ -135,6 +140,7 @@ async fn test_missing_header_bearer_prefix_is_rejected() {
     let expected_body = JsonStatus {
         reason: Some(String::from("Unauthorized")),
         detail: None,
+        id: None,
     };
 
     assert_eq!(response_body, expected_body);
@@ -154,6 +160,7 @@ async fn test_wrong_claims_type_is_rejected() {
         )
         .unwrap(),
         omn_cl_typ: "illegal".to_string(),
+        ..SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap()
     });
 
     let app = app(state).into_service();
@@ -179,6 +186,7 @@ async fn test_wrong_claims_type_is_rejected() {
     let expected_body = JsonStatus {
         reason: Some(String::from("Unauthorized")),
         detail: None,
+        id: None,
     };
 
     assert_eq!(response_body, expected_body);
@@ -208,7 +216,150 @@ async fn test_missing_session_header_is_rejected() {
     let expected_body = JsonStatus {
         reason: Some(String::from("Unauthorized")),
         detail: None,
+        id: None,
     };
 
     assert_eq!(response_body, expected_body);
 }
+
+fn status_app(state: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move {
+                    format!("Hello, {}!", caller.name)
+                }),
+            )
+            .route(
+                "/api/account/verify",
+                get(|Extension(status): Extension<AccountStatus>| async move {
+                    format!("{:?}", status)
+                }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+fn status_sessions(status: AccountStatus) -> FakeSessionManager<FakeAccount> {
+    let sessions = FakeSessionManager::new().with_unverified_route("/api/account/verify");
+    sessions.insert_account_with_status(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+        status,
+    );
+    sessions
+}
+
+#[tokio::test]
+async fn test_active_account_is_authorized() {
+    let sessions = status_sessions(AccountStatus::Active);
+    let session = sessions.mint_session("test-account-id");
+    let client = status_app(sessions);
+
+    let response = client.get("/api/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Test Account!");
+}
+
+#[tokio::test]
+async fn test_suspended_account_is_forbidden() {
+    let sessions = status_sessions(AccountStatus::Suspended);
+    let session = sessions.mint_session("test-account-id");
+    let client = status_app(sessions);
+
+    for path in ["/api/account", "/api/account/verify"] {
+        client
+            .get(path)
+            .bearer(&session)
+            .send()
+            .await
+            .assert_status_body(StatusCode::FORBIDDEN, Some(ACCOUNT_SUSPENDED_DETAIL));
+    }
+}
+
+#[tokio::test]
+async fn test_unverified_account_is_limited_to_allowed_routes() {
+    let sessions = status_sessions(AccountStatus::Unverified);
+    let session = sessions.mint_session("test-account-id");
+    let client = status_app(sessions);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status_body(StatusCode::FORBIDDEN, Some(VERIFICATION_REQUIRED_DETAIL));
+
+    let response = client
+        .get("/api/account/verify")
+        .bearer(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Unverified");
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct OrgClaims {
+    org_id: String,
+    display_name: String,
+}
+
+#[tokio::test]
+async fn test_custom_claims_are_exposed_to_handlers() {
+    let sessions = FakeSessionManager::new().with_account(
+        "test-account-id",
+        FakeAccount {
+            name: "Test Account".into(),
+        },
+    );
+    let session = sessions.mint_session_with_claims(
+        "test-account-id",
+        OrgClaims {
+            org_id: "test-org-id".into(),
+            display_name: "Tester".into(),
+        },
+    );
+    let client = TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|CustomClaims(claims): CustomClaims<OrgClaims>| async move {
+                    format!("Hello, {} of {}!", claims.display_name, claims.org_id)
+                }),
+            )
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                resolve_with_claims::<FakeAccount, OrgClaims, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(sessions.clone()),
+    );
+
+    let response = client.get("/api/account").bearer(&session).send().await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Tester of test-org-id!");
+
+    client
+        .get("/api/account")
+        .bearer(&sessions.mint_session("test-account-id"))
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    client
+        .get("/api/account")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
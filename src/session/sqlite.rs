@@ -0,0 +1,220 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::purpose::ConsumedTokenStore;
+use crate::session::store::{SessionStore, StoredSession};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS omnium_sessions (
+    id_hash TEXT PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+)";
+
+const CREATE_CONSUMED_TOKENS_TABLE: &str = "CREATE TABLE IF NOT EXISTS omnium_consumed_tokens (
+    jti TEXT PRIMARY KEY NOT NULL,
+    expires_at INTEGER NOT NULL
+)";
+
+/// Sessions are stored by a SHA-256 hash of their id, so the ids in a leaked database can't be
+/// used as credentials.
+fn id_hash(session_id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(session_id.as_bytes()))
+}
+
+/// SQLite-backed `SessionStore`, storing sessions in an `omnium_sessions` table created on open,
+/// keyed by a hash of the session id. Also a `ConsumedTokenStore`, in an `omnium_consumed_tokens`
+/// table. Queries run on the blocking thread pool.
+#[derive(Clone)]
+pub struct SqliteSessionStore {
+    connection: Arc<Mutex<Connection>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SqliteSessionStore {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<SqliteSessionStore> {
+        SqliteSessionStore::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> anyhow::Result<SqliteSessionStore> {
+        SqliteSessionStore::from_connection(Connection::open_in_memory()?)
+    }
+
+    pub fn from_connection(connection: Connection) -> anyhow::Result<SqliteSessionStore> {
+        connection.execute(CREATE_TABLE, [])?;
+        connection.execute(CREATE_CONSUMED_TOKENS_TABLE, [])?;
+        Ok(SqliteSessionStore {
+            connection: Arc::new(Mutex::new(connection)),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Expires sessions by the clock, instead of `SystemClock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Current time in seconds since the Unix epoch, as stored in SQLite integer columns.
+    fn now(&self) -> i64 {
+        i64::try_from(self.clock.now_secs()).unwrap_or(i64::MAX)
+    }
+
+    async fn with_connection<R, F>(&self, query: F) -> anyhow::Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&Connection) -> anyhow::Result<R> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection.lock().unwrap_or_else(|err| err.into_inner());
+            query(&connection)
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl SessionStore for SqliteSessionStore {
+    async fn create(
+        &self,
+        account_id: &str,
+        data: Value,
+        ttl: Duration,
+    ) -> anyhow::Result<StoredSession> {
+        let session = StoredSession::new_at(self.clock.as_ref(), account_id, data, ttl)?;
+        let id_hash = id_hash(&session.id);
+        let stored = session.clone();
+        let now = self.now();
+
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM omnium_sessions WHERE expires_at < ?1",
+                params![now],
+            )?;
+            connection.execute(
+                "INSERT INTO omnium_sessions (id_hash, account_id, expires_at, data) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    id_hash,
+                    stored.account_id,
+                    i64::try_from(stored.expires_at)?,
+                    serde_json::to_string(&stored.data)?
+                ],
+            )?;
+            Ok(())
+        })
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn load(&self, session_id: &str) -> anyhow::Result<Option<StoredSession>> {
+        let session_id = session_id.to_string();
+        let id_hash = id_hash(&session_id);
+        let now = self.now();
+
+        self.with_connection(move |connection| {
+            let row = connection
+                .query_row(
+                    "SELECT account_id, expires_at, data FROM omnium_sessions WHERE id_hash = ?1 AND expires_at >= ?2",
+                    params![id_hash, now],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    },
+                )
+                .optional()?;
+
+            match row {
+                Some((account_id, expires_at, data)) => Ok(Some(StoredSession {
+                    id: session_id,
+                    account_id,
+                    expires_at: u64::try_from(expires_at)?,
+                    data: serde_json::from_str(&data)?,
+                })),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    async fn update(&self, session_id: &str, data: Value) -> anyhow::Result<bool> {
+        let id_hash = id_hash(session_id);
+        let now = self.now();
+
+        self.with_connection(move |connection| {
+            let updated = connection.execute(
+                "UPDATE omnium_sessions SET data = ?1 WHERE id_hash = ?2 AND expires_at >= ?3",
+                params![serde_json::to_string(&data)?, id_hash, now],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn touch(&self, session_id: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let id_hash = id_hash(session_id);
+        let now = self.now();
+
+        self.with_connection(move |connection| {
+            let updated = connection.execute(
+                "UPDATE omnium_sessions SET expires_at = ?1 WHERE id_hash = ?2 AND expires_at >= ?3",
+                params![
+                    now.saturating_add(i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX)),
+                    id_hash,
+                    now
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn destroy(&self, session_id: &str) -> anyhow::Result<()> {
+        let id_hash = id_hash(session_id);
+
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM omnium_sessions WHERE id_hash = ?1",
+                params![id_hash],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ConsumedTokenStore for SqliteSessionStore {
+    async fn consume(&self, jti: &str, expires_at: u64) -> anyhow::Result<bool> {
+        let jti = jti.to_string();
+        let expires_at = i64::try_from(expires_at)?;
+        let now = self.now();
+
+        self.with_connection(move |connection| {
+            connection.execute(
+                "DELETE FROM omnium_consumed_tokens WHERE expires_at < ?1",
+                params![now],
+            )?;
+            let inserted = connection.execute(
+                "INSERT OR IGNORE INTO omnium_consumed_tokens (jti, expires_at) VALUES (?1, ?2)",
+                params![jti, expires_at],
+            )?;
+            Ok(inserted > 0)
+        })
+        .await
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde_json::json;
+
+use crate::session::purpose::ConsumedTokenStore;
+use crate::session::sqlite::SqliteSessionStore;
+use crate::session::store::SessionStore;
+
+#[tokio::test]
+async fn test_sqlite_store_round_trips_sessions() {
+    let store = SqliteSessionStore::open_in_memory().unwrap();
+    let session = store
+        .create(
+            "test-account-id",
+            json!({ "theme": "dark" }),
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        store.load(&session.id).await.unwrap(),
+        Some(session.clone())
+    );
+
+    assert!(store
+        .update(&session.id, json!({ "theme": "light" }))
+        .await
+        .unwrap());
+    assert!(store
+        .touch(&session.id, Duration::from_secs(120))
+        .await
+        .unwrap());
+    let loaded = store.load(&session.id).await.unwrap().unwrap();
+    assert_eq!(loaded.data, json!({ "theme": "light" }));
+    assert!(loaded.expires_at >= session.expires_at + 60);
+
+    store.destroy(&session.id).await.unwrap();
+    assert!(store.load(&session.id).await.unwrap().is_none());
+    assert!(!store.update(&session.id, json!(null)).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sqlite_store_ignores_expired_sessions() {
+    let store = SqliteSessionStore::open_in_memory().unwrap();
+    let session = store
+        .create("test-account-id", json!(null), Duration::ZERO)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(store.load(&session.id).await.unwrap().is_none());
+    assert!(!store
+        .touch(&session.id, Duration::from_secs(60))
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_sqlite_store_consumes_tokens_once() {
+    let store = SqliteSessionStore::open_in_memory().unwrap();
+
+    assert!(store.consume("test-jti", 4_000_000_000).await.unwrap());
+    assert!(!store.consume("test-jti", 4_000_000_000).await.unwrap());
+    assert!(store.consume("other-jti", 4_000_000_000).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sqlite_store_keeps_only_hashed_session_ids() {
+    let path = std::env::temp_dir().join(format!("omnium-{}.db", uuid::Uuid::new_v4()));
+    let store = SqliteSessionStore::open(&path).unwrap();
+    let session = store
+        .create("test-account-id", json!(null), Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    let stored: Vec<String> = Connection::open(&path)
+        .unwrap()
+        .prepare("SELECT id_hash FROM omnium_sessions")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(stored.len(), 1);
+    assert_ne!(stored[0], session.id);
+    assert_eq!(
+        store
+            .load(&session.id)
+            .await
+            .unwrap()
+            .map(|loaded| loaded.id),
+        Some(session.id)
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    CookieJar,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::session::{Credential, SessionClaims, SessionManager, SESSION_CLAIMS_TYPE};
+
+const SESSION_ID_BYTES: usize = 32;
+
+/// Generates an opaque session id from 256 bits of OS randomness.
+pub fn generate_session_id() -> anyhow::Result<String> {
+    let mut bytes = [0u8; SESSION_ID_BYTES];
+    getrandom::fill(&mut bytes).map_err(|err| anyhow::anyhow!("{}", err))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// A server-side session, looked up by its opaque id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub id: String,
+    pub account_id: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires_at: u64,
+    pub data: Value,
+}
+
+impl StoredSession {
+    pub fn new(account_id: &str, data: Value, ttl: Duration) -> anyhow::Result<StoredSession> {
+        StoredSession::new_at(&SystemClock, account_id, data, ttl)
+    }
+
+    /// A session expiring `ttl` from the current time of the clock.
+    pub fn new_at(
+        clock: &dyn Clock,
+        account_id: &str,
+        data: Value,
+        ttl: Duration,
+    ) -> anyhow::Result<StoredSession> {
+        Ok(StoredSession {
+            id: generate_session_id()?,
+            account_id: account_id.into(),
+            expires_at: clock.now_secs().saturating_add(ttl.as_secs()),
+            data,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(&SystemClock)
+    }
+
+    pub fn is_expired_at(&self, clock: &dyn Clock) -> bool {
+        self.expires_at < clock.now_secs()
+    }
+}
+
+/// Storage for server-side sessions. Implementations must not return expired sessions from
+/// `load`.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn create(
+        &self,
+        account_id: &str,
+        data: Value,
+        ttl: Duration,
+    ) -> anyhow::Result<StoredSession>;
+
+    async fn load(&self, session_id: &str) -> anyhow::Result<Option<StoredSession>>;
+
+    /// Replaces the data of a live session, returning whether it exists.
+    async fn update(&self, session_id: &str, data: Value) -> anyhow::Result<bool>;
+
+    /// Extends the expiry of a live session to `ttl` from now, returning whether it exists.
+    async fn touch(&self, session_id: &str, ttl: Duration) -> anyhow::Result<bool>;
+
+    async fn destroy(&self, session_id: &str) -> anyhow::Result<()>;
+}
+
+/// In-memory `SessionStore`, pruning expired sessions as new sessions are created.
+#[derive(Clone)]
+pub struct MemorySessionStore {
+    sessions: Arc<RwLock<HashMap<String, StoredSession>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MemorySessionStore {
+    fn default() -> Self {
+        MemorySessionStore {
+            sessions: Arc::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl MemorySessionStore {
+    pub fn new() -> MemorySessionStore {
+        MemorySessionStore::default()
+    }
+
+    /// Expires sessions by the clock, instead of `SystemClock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn create(
+        &self,
+        account_id: &str,
+        data: Value,
+        ttl: Duration,
+    ) -> anyhow::Result<StoredSession> {
+        let session = StoredSession::new_at(self.clock.as_ref(), account_id, data, ttl)?;
+
+        let mut sessions = self.sessions.write().unwrap_or_else(|err| err.into_inner());
+        sessions.retain(|_, session| !session.is_expired_at(self.clock.as_ref()));
+        sessions.insert(session.id.clone(), session.clone());
+
+        Ok(session)
+    }
+
+    async fn load(&self, session_id: &str) -> anyhow::Result<Option<StoredSession>> {
+        Ok(self
+            .sessions
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(session_id)
+            .filter(|session| !session.is_expired_at(self.clock.as_ref()))
+            .cloned())
+    }
+
+    async fn update(&self, session_id: &str, data: Value) -> anyhow::Result<bool> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|err| err.into_inner());
+        match sessions
+            .get_mut(session_id)
+            .filter(|session| !session.is_expired_at(self.clock.as_ref()))
+        {
+            Some(session) => {
+                session.data = data;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn touch(&self, session_id: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|err| err.into_inner());
+        match sessions
+            .get_mut(session_id)
+            .filter(|session| !session.is_expired_at(self.clock.as_ref()))
+        {
+            Some(session) => {
+                session.expires_at = self.clock.now_secs().saturating_add(ttl.as_secs());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn destroy(&self, session_id: &str) -> anyhow::Result<()> {
+        self.sessions
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(session_id);
+        Ok(())
+    }
+}
+
+/// Account lookup for a `StoreSessionManager`, typically implemented on the application state.
+#[async_trait]
+pub trait AccountLookup<U>: Send + Sync {
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>>;
+}
+
+/// A `SessionManager` resolving opaque session ids from a cookie through a `SessionStore`, instead
+/// of decoding claims from the credential. Sessions can be revoked instantly by destroying them.
+pub struct StoreSessionManager<St, L> {
+    store: Arc<St>,
+    lookup: L,
+    cookie_name: String,
+    ttl: Duration,
+    sliding: bool,
+    clock: Arc<dyn Clock>,
+}
+
+impl<St, L: Clone> Clone for StoreSessionManager<St, L> {
+    fn clone(&self) -> Self {
+        StoreSessionManager {
+            store: self.store.clone(),
+            lookup: self.lookup.clone(),
+            cookie_name: self.cookie_name.clone(),
+            ttl: self.ttl,
+            sliding: self.sliding,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<St, L> StoreSessionManager<St, L>
+where
+    St: SessionStore,
+{
+    pub fn new(
+        store: St,
+        lookup: L,
+        cookie_name: &str,
+        ttl: Duration,
+    ) -> StoreSessionManager<St, L> {
+        StoreSessionManager {
+            store: Arc::new(store),
+            lookup,
+            cookie_name: cookie_name.into(),
+            ttl,
+            sliding: false,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Extends the expiry of a session to the full ttl each time it is used. The session cookie is
+    /// then set without a `Max-Age`, lasting for the browser session, so the browser doesn't drop
+    /// it at the original expiry while the store keeps extending it.
+    pub fn with_sliding_expiry(mut self) -> Self {
+        self.sliding = true;
+        self
+    }
+
+    /// Validates sessions by the clock, instead of `SystemClock`. The store should share it.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn store(&self) -> &St {
+        &self.store
+    }
+
+    fn cookie(&self, value: String, max_age: Option<Duration>) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.cookie_name.clone(), value))
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax)
+            .path("/");
+        if let Some(max_age) = max_age {
+            cookie = cookie.max_age(max_age.try_into().unwrap_or_default());
+        }
+        cookie.build()
+    }
+
+    /// Creates a session for the account, returning it with the cookie to set on the response.
+    pub async fn create_session(
+        &self,
+        account_id: &str,
+        data: Value,
+    ) -> anyhow::Result<(StoredSession, Cookie<'static>)> {
+        let session = self.store.create(account_id, data, self.ttl).await?;
+        let max_age = (!self.sliding).then_some(self.ttl);
+        let cookie = self.cookie(session.id.clone(), max_age);
+        Ok((session, cookie))
+    }
+
+    /// Loads the live session for the request's cookie, if any.
+    pub async fn current_session(
+        &self,
+        cookies: &CookieJar,
+    ) -> anyhow::Result<Option<StoredSession>> {
+        match Credential::from_cookie(&self.cookie_name, cookies) {
+            Some(Credential(session_id)) => self.store.load(&session_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Loads the live session of the credential as claims carrying the session data, sliding its
+    /// expiry if enabled.
+    async fn load_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<Value>> {
+        let mut session = self
+            .store
+            .load(&credential.0)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+
+        if self.sliding && self.store.touch(&session.id, self.ttl).await? {
+            session.expires_at = self.clock.now_secs().saturating_add(self.ttl.as_secs());
+        }
+
+        Ok(SessionClaims {
+            sub: session.account_id,
+            exp: usize::try_from(session.expires_at)?,
+            omn_cl_typ: SESSION_CLAIMS_TYPE.into(),
+            iss: None,
+            aud: None,
+            iat: None,
+            nbf: None,
+            jti: None,
+            tenant: None,
+            act: None,
+            custom: session.data,
+        })
+    }
+
+    /// Destroys the session for the request's cookie, if any, returning the cookie to set on the
+    /// response to clear it.
+    pub async fn destroy_session(&self, cookies: &CookieJar) -> anyhow::Result<Cookie<'static>> {
+        if let Some(Credential(session_id)) = Credential::from_cookie(&self.cookie_name, cookies) {
+            self.store.destroy(&session_id).await?;
+        }
+        Ok(self.cookie(String::new(), Some(Duration::ZERO)))
+    }
+}
+
+#[async_trait]
+impl<U, St, L> SessionManager<U> for StoreSessionManager<St, L>
+where
+    St: SessionStore,
+    L: AccountLookup<U>,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        Ok(self.load_claims(credential).await?.with_custom(()))
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        self.lookup.get_account(account_id).await
+    }
+
+    fn extract_credential(&self, _request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        Credential::from_cookie(&self.cookie_name, cookies)
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
+
+/// Resolves the session data as custom claims, for `resolve_with_claims::<U, Value, _>`, so
+/// handlers read it as `CustomClaims<Value>` without loading the session again.
+#[async_trait]
+impl<U, St, L> SessionManager<U, Value> for StoreSessionManager<St, L>
+where
+    St: SessionStore,
+    L: AccountLookup<U>,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<Value>> {
+        self.load_claims(credential).await
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        self.lookup.get_account(account_id).await
+    }
+
+    fn extract_credential(&self, _request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        Credential::from_cookie(&self.cookie_name, cookies)
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use hyper::StatusCode;
+use serde_json::{json, Value};
+
+use crate::session::session::{authorize, resolve, resolve_with_claims, CustomClaims};
+use crate::session::store::{AccountLookup, MemorySessionStore, SessionStore, StoreSessionManager};
+use crate::testing::client::TestClient;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+#[derive(Clone)]
+struct FakeAccounts;
+
+#[async_trait]
+impl AccountLookup<FakeAccount> for FakeAccounts {
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(match account_id.as_str() {
+            "test-account-id" => Some(FakeAccount {
+                name: "Test Account".into(),
+            }),
+            _ => None,
+        })
+    }
+}
+
+type Manager = StoreSessionManager<MemorySessionStore, FakeAccounts>;
+
+fn sessions() -> Manager {
+    StoreSessionManager::new(
+        MemorySessionStore::new(),
+        FakeAccounts,
+        "session",
+        Duration::from_secs(60),
+    )
+}
+
+fn app(state: Manager) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move {
+                    format!("Hello, {}!", caller.name)
+                }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, Manager>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, Manager>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[tokio::test]
+async fn test_created_session_is_resolved_from_cookie() {
+    let sessions = sessions();
+    let (session, cookie) = sessions
+        .create_session("test-account-id", json!({ "theme": "dark" }))
+        .await
+        .unwrap();
+    let client = app(sessions);
+
+    assert_eq!(cookie.name(), "session");
+    assert_eq!(cookie.value(), session.id);
+    assert_eq!(cookie.http_only(), Some(true));
+
+    let response = client
+        .get("/api/account")
+        .cookie("session", &session.id)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Test Account!");
+
+    client
+        .get("/api/account")
+        .cookie("session", "unknown-session-id")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_destroyed_session_is_rejected() {
+    let sessions = sessions();
+    let (session, _) = sessions
+        .create_session("test-account-id", json!(null))
+        .await
+        .unwrap();
+    let client = app(sessions.clone());
+
+    let jar = CookieJar::new().add(Cookie::new("session", session.id.clone()));
+    let cleared = sessions.destroy_session(&jar).await.unwrap();
+    assert_eq!(cleared.value(), "");
+    assert!(sessions.current_session(&jar).await.unwrap().is_none());
+
+    client
+        .get("/api/account")
+        .cookie("session", &session.id)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_memory_store_updates_and_expires_sessions() {
+    let store = MemorySessionStore::new();
+    let session = store
+        .create(
+            "test-account-id",
+            json!({ "step": 1 }),
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+    assert!(store
+        .update(&session.id, json!({ "step": 2 }))
+        .await
+        .unwrap());
+    assert_eq!(
+        store.load(&session.id).await.unwrap().unwrap().data,
+        json!({ "step": 2 })
+    );
+    assert!(!store.update("unknown", json!(null)).await.unwrap());
+
+    let expired = store
+        .create("test-account-id", json!(null), Duration::ZERO)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(store.load(&expired.id).await.unwrap().is_none());
+    assert!(!store
+        .touch(&expired.id, Duration::from_secs(60))
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_session_ids_are_unique() {
+    let store = MemorySessionStore::new();
+    let first = store
+        .create("test-account-id", json!(null), Duration::from_secs(60))
+        .await
+        .unwrap();
+    let second = store
+        .create("test-account-id", json!(null), Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    assert_ne!(first.id, second.id);
+    assert_eq!(first.id.len(), 43);
+}
+
+#[tokio::test]
+async fn test_sliding_session_cookie_has_no_max_age() {
+    let (_, cookie) = sessions()
+        .create_session("test-account-id", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(
+        cookie.max_age().map(|max_age| max_age.whole_seconds()),
+        Some(60)
+    );
+
+    let (_, cookie) = sessions()
+        .with_sliding_expiry()
+        .create_session("test-account-id", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(cookie.max_age(), None);
+}
+
+#[tokio::test]
+async fn test_session_data_reaches_handlers_as_custom_claims() {
+    let sessions = sessions();
+    let (session, _) = sessions
+        .create_session("test-account-id", json!({ "theme": "dark" }))
+        .await
+        .unwrap();
+    let client = TestClient::new(
+        Router::new()
+            .route(
+                "/api/theme",
+                get(|CustomClaims(data): CustomClaims<Value>| async move {
+                    data["theme"].as_str().unwrap_or_default().to_string()
+                }),
+            )
+            .layer(from_fn_with_state(
+                sessions.clone(),
+                resolve_with_claims::<FakeAccount, Value, Manager>,
+            ))
+            .with_state(sessions),
+    );
+
+    let response = client
+        .get("/api/theme")
+        .cookie("session", &session.id)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "dark");
+}
+
+#[tokio::test]
+async fn test_long_ttl_saturates_expiry() {
+    let store = MemorySessionStore::new();
+    let session = store
+        .create("test-account-id", json!(null), Duration::MAX)
+        .await
+        .unwrap();
+    assert_eq!(session.expires_at, u64::MAX);
+
+    assert!(store.touch(&session.id, Duration::MAX).await.unwrap());
+}
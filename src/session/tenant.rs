@@ -0,0 +1,67 @@
+use axum::extract::Request;
+use hyper::header::{HeaderName, HOST};
+
+/// The tenant of a request, inserted into the request extensions by `resolve`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tenant(pub String);
+
+impl Tenant {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Where to find the tenant of a request, for `SessionManager::resolve_tenant`.
+#[derive(Clone, Debug)]
+pub enum TenantSource {
+    /// The subdomain of the `host` header under the base domain, such as `acme` for
+    /// `acme.example.com` under `example.com`.
+    Subdomain(String),
+    /// The value of a request header, such as `x-tenant-id`.
+    Header(HeaderName),
+    /// The path segment following the prefix, such as `acme` for `/tenants/acme/api/account`
+    /// under `/tenants`.
+    PathPrefix(String),
+}
+
+impl TenantSource {
+    pub fn resolve(&self, request: &Request) -> Option<Tenant> {
+        // Host names are case-insensitive, so the host is matched against the base domain in
+        // lowercase.
+        let host: String;
+        let tenant = match self {
+            TenantSource::Subdomain(base_domain) => {
+                // HTTP/2 requests carry the host in the `:authority` of the URI, and may have no
+                // `host` header.
+                host = request
+                    .headers()
+                    .get(HOST)
+                    .and_then(|host| host.to_str().ok())
+                    .or_else(|| request.uri().host())?
+                    .to_ascii_lowercase();
+                let host = host.split(':').next()?;
+                let subdomain = host
+                    .strip_suffix(&base_domain.trim_start_matches('.').to_ascii_lowercase())?
+                    .strip_suffix('.')?;
+                match subdomain.contains('.') {
+                    true => return None,
+                    false => subdomain,
+                }
+            }
+            TenantSource::Header(name) => request.headers().get(name)?.to_str().ok()?.trim(),
+            TenantSource::PathPrefix(prefix) => request
+                .uri()
+                .path()
+                .strip_prefix(prefix.trim_end_matches('/'))?
+                .strip_prefix('/')?
+                .split('/')
+                .next()?,
+        };
+
+        let valid = !tenant.is_empty()
+            && tenant
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| Tenant(tenant.to_ascii_lowercase()))
+    }
+}
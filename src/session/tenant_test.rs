@@ -0,0 +1,292 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use axum_extra::extract::CookieJar;
+use hyper::{header::HeaderName, StatusCode};
+
+use crate::session::session::{
+    authorize, resolve, AccountStatus, Credential, SessionClaims, SessionManager,
+};
+use crate::session::tenant::{Tenant, TenantSource};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+fn request(host: &str, path: &str) -> Request {
+    Request::builder()
+        .uri(path)
+        .header("host", host)
+        .header("x-tenant-id", "Acme")
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn tenant(id: &str) -> Option<Tenant> {
+    Some(Tenant(id.into()))
+}
+
+#[test]
+fn test_tenant_from_subdomain() {
+    let source = TenantSource::Subdomain("example.com".into());
+
+    assert_eq!(
+        source.resolve(&request("acme.example.com", "/")),
+        tenant("acme")
+    );
+    assert_eq!(
+        source.resolve(&request("acme.example.com:8080", "/")),
+        tenant("acme")
+    );
+    assert_eq!(source.resolve(&request("example.com", "/")), None);
+    assert_eq!(source.resolve(&request("a.b.example.com", "/")), None);
+    assert_eq!(source.resolve(&request("acme.example.org", "/")), None);
+}
+
+#[test]
+fn test_tenant_from_subdomain_ignores_host_case() {
+    assert_eq!(
+        TenantSource::Subdomain("example.com".into()).resolve(&request("ACME.Example.COM", "/")),
+        tenant("acme")
+    );
+    assert_eq!(
+        TenantSource::Subdomain("Example.com".into()).resolve(&request("acme.example.com", "/")),
+        tenant("acme")
+    );
+}
+
+#[test]
+fn test_tenant_from_subdomain_of_uri_without_host_header() {
+    let request = Request::builder()
+        .uri("https://acme.example.com/api/account")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        TenantSource::Subdomain("example.com".into()).resolve(&request),
+        tenant("acme")
+    );
+}
+
+#[test]
+fn test_tenant_from_header() {
+    let source = TenantSource::Header(HeaderName::from_static("x-tenant-id"));
+
+    assert_eq!(source.resolve(&request("example.com", "/")), tenant("acme"));
+    assert_eq!(
+        TenantSource::Header(HeaderName::from_static("x-other"))
+            .resolve(&request("example.com", "/")),
+        None
+    );
+}
+
+#[test]
+fn test_tenant_from_path_prefix() {
+    let source = TenantSource::PathPrefix("/tenants".into());
+
+    assert_eq!(
+        source.resolve(&request("example.com", "/tenants/acme/api/account")),
+        tenant("acme")
+    );
+    assert_eq!(
+        source.resolve(&request("example.com", "/tenants/acme")),
+        tenant("acme")
+    );
+    assert_eq!(source.resolve(&request("example.com", "/tenants/")), None);
+    assert_eq!(
+        source.resolve(&request("example.com", "/tenantsacme/api")),
+        None
+    );
+    assert_eq!(
+        source.resolve(&request("example.com", "/api/account")),
+        None
+    );
+}
+
+fn app(state: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(
+                    |Extension(caller): Extension<FakeAccount>,
+                     Extension(tenant): Extension<Tenant>| async move {
+                        format!("Hello, {} of {}!", caller.name, tenant.0)
+                    },
+                ),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+fn sessions() -> FakeSessionManager<FakeAccount> {
+    FakeSessionManager::new()
+        .with_tenant_source(TenantSource::Header(HeaderName::from_static("x-tenant-id")))
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+}
+
+#[tokio::test]
+async fn test_session_is_accepted_for_its_tenant() {
+    let sessions = sessions();
+    let session = sessions.mint_tenant_session(&Tenant("acme".into()), "test-account-id");
+    let client = app(sessions);
+
+    let response = client
+        .get("/api/account")
+        .header(HeaderName::from_static("x-tenant-id"), "acme")
+        .bearer(&session)
+        .send()
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "Hello, Test Account of acme!");
+}
+
+#[tokio::test]
+async fn test_session_minted_for_mixed_case_tenant_is_accepted() {
+    let sessions = sessions();
+    let session = sessions.mint_tenant_session(&Tenant("Acme".into()), "test-account-id");
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .header(HeaderName::from_static("x-tenant-id"), "ACME")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_session_is_rejected_for_another_tenant() {
+    let sessions = sessions();
+    let session = sessions.mint_tenant_session(&Tenant("acme".into()), "test-account-id");
+    let untenanted = sessions.mint_session("test-account-id");
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .header(HeaderName::from_static("x-tenant-id"), "globex")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    client
+        .get("/api/account")
+        .header(HeaderName::from_static("x-tenant-id"), "acme")
+        .bearer(&untenanted)
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[derive(Clone)]
+struct TenantAccounts {
+    accounts: Arc<HashMap<(String, String), FakeAccount>>,
+}
+
+#[async_trait]
+impl SessionManager<FakeAccount> for TenantAccounts {
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims> {
+        Ok(serde_json::from_str(&credential.0)?)
+    }
+
+    async fn get_account(&self, _account_id: String) -> anyhow::Result<Option<FakeAccount>> {
+        Ok(None)
+    }
+
+    async fn get_tenant_account(
+        &self,
+        tenant: Option<&Tenant>,
+        account_id: String,
+    ) -> anyhow::Result<Option<(FakeAccount, AccountStatus)>> {
+        let Some(tenant) = tenant else {
+            return Ok(None);
+        };
+        Ok(self
+            .accounts
+            .get(&(tenant.0.clone(), account_id))
+            .map(|account| (account.clone(), AccountStatus::Active)))
+    }
+
+    fn extract_credential(&self, request: &Request, _cookies: &CookieJar) -> Option<Credential> {
+        Credential::from_authorization_header(request)
+    }
+
+    fn resolve_tenant(&self, request: &Request) -> Option<Tenant> {
+        TenantSource::Subdomain("example.com".into()).resolve(request)
+    }
+}
+
+#[tokio::test]
+async fn test_account_is_looked_up_in_tenant() {
+    let account = |name: &str| FakeAccount { name: name.into() };
+    let state = TenantAccounts {
+        accounts: Arc::new(HashMap::from([
+            (("acme".into(), "1".into()), account("Acme Account")),
+            (("globex".into(), "1".into()), account("Globex Account")),
+        ])),
+    };
+    let client = TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move { caller.name }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, TenantAccounts>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, TenantAccounts>,
+            ))
+            .with_state(state),
+    );
+
+    for tenant in ["acme", "globex"] {
+        let session = FakeSessionManager::<FakeAccount>::encode_claims(
+            &SessionClaims::new("1", std::time::Duration::from_secs(60), ())
+                .unwrap()
+                .with_tenant(&Tenant(tenant.into())),
+        );
+        let response = client
+            .get("/api/account")
+            .header(
+                HeaderName::from_static("host"),
+                format!("{}.example.com", tenant).as_str(),
+            )
+            .bearer(&session)
+            .send()
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert!(response.text().to_lowercase().starts_with(tenant));
+    }
+}
@@ -0,0 +1,55 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+
+const HS256: &str = "HS256";
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    typ: String,
+}
+
+pub(crate) fn sign(key: &[u8], message: &str) -> anyhow::Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// Encodes claims as a JWT signed with HMAC-SHA256.
+pub fn encode_hs256<C: Serialize>(claims: &C, key: &[u8]) -> anyhow::Result<String> {
+    let header = Header {
+        alg: HS256.into(),
+        typ: "JWT".into(),
+    };
+
+    let message = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?)
+    );
+    let signature = URL_SAFE_NO_PAD.encode(sign(key, &message)?.finalize().into_bytes());
+
+    Ok(format!("{}.{}", message, signature))
+}
+
+/// Decodes the claims of a JWT signed with HMAC-SHA256, verifying the signature. Registered claims,
+/// such as `exp`, are not validated here, but by `ClaimsValidation` in `resolve`.
+pub fn decode_hs256<C: DeserializeOwned>(token: &str, key: &[u8]) -> anyhow::Result<C> {
+    let (message, signature) = token
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow::anyhow!("Malformed token"))?;
+    let (header, claims) = message
+        .split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("Malformed token"))?;
+
+    let header: Header = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?;
+    if header.alg != HS256 {
+        anyhow::bail!("Unsupported token algorithm: {}", header.alg);
+    }
+
+    sign(key, message)?.verify_slice(&URL_SAFE_NO_PAD.decode(signature)?)?;
+
+    Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims)?)?)
+}
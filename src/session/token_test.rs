@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::session::session::SessionClaims;
+use crate::session::token::{decode_hs256, encode_hs256};
+
+const KEY: &[u8] = b"test-signing-key";
+
+fn claims() -> SessionClaims {
+    SessionClaims::new("test-account-id", Duration::from_secs(60), ()).unwrap()
+}
+
+#[test]
+fn test_token_round_trip() {
+    let token = encode_hs256(&claims(), KEY).unwrap();
+
+    assert_eq!(token.split('.').count(), 3);
+
+    let decoded: SessionClaims = decode_hs256(&token, KEY).unwrap();
+    assert_eq!(decoded.sub, "test-account-id");
+    assert_eq!(decoded.omn_cl_typ, "session");
+}
+
+#[test]
+fn test_wrong_key_is_rejected() {
+    let token = encode_hs256(&claims(), KEY).unwrap();
+
+    assert!(decode_hs256::<SessionClaims>(&token, b"other-key").is_err());
+}
+
+#[test]
+fn test_tampered_claims_are_rejected() {
+    let token = encode_hs256(&claims(), KEY).unwrap();
+    let parts: Vec<&str> = token.split('.').collect();
+
+    let mut tampered = claims();
+    tampered.sub = "other-account-id".into();
+    let tampered = format!(
+        "{}.{}.{}",
+        parts[0],
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&tampered).unwrap()),
+        parts[2]
+    );
+
+    assert!(decode_hs256::<SessionClaims>(&tampered, KEY).is_err());
+}
+
+#[test]
+fn test_unsigned_token_is_rejected() {
+    let token = format!(
+        "{}.{}.",
+        URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#),
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims()).unwrap())
+    );
+
+    assert!(decode_hs256::<SessionClaims>(&token, KEY).is_err());
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct OrgClaims {
+    org_id: String,
+    cohorts: Vec<String>,
+}
+
+#[test]
+fn test_custom_claims_are_flattened() {
+    let claims = SessionClaims::new(
+        "test-account-id",
+        Duration::from_secs(60),
+        OrgClaims {
+            org_id: "test-org-id".into(),
+            cohorts: vec!["beta".into()],
+        },
+    )
+    .unwrap();
+    let token = encode_hs256(&claims, KEY).unwrap();
+
+    let payload = token.split('.').nth(1).unwrap();
+    let payload: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+    assert_eq!(payload["org_id"], "test-org-id");
+    assert_eq!(payload["sub"], "test-account-id");
+
+    let decoded: SessionClaims<OrgClaims> = decode_hs256(&token, KEY).unwrap();
+    assert_eq!(decoded.custom, claims.custom);
+
+    let decoded: SessionClaims = decode_hs256(&token, KEY).unwrap();
+    assert_eq!(decoded.sub, "test-account-id");
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::session::session::SessionClaims;
+
+pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+static DEFAULT_CLAIMS_VALIDATION: ClaimsValidation = ClaimsValidation::new();
+
+/// The `aud` claim, either a single audience or a list of audiences.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::One(one) => one == audience,
+            Audience::Many(many) => many.iter().any(|one| one == audience),
+        }
+    }
+}
+
+/// Why `ClaimsValidation` rejected a set of claims.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimsError {
+    Expired,
+    NotYetValid,
+    IssuedInFuture,
+    MissingIssuedAt,
+    TooOld,
+    InvalidIssuer,
+    InvalidAudience,
+}
+
+impl ClaimsError {
+    /// The error as a `resolve` span outcome, such as `claims_expired`.
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            ClaimsError::Expired => "claims_expired",
+            ClaimsError::NotYetValid => "claims_not_yet_valid",
+            ClaimsError::IssuedInFuture => "claims_issued_in_future",
+            ClaimsError::MissingIssuedAt => "claims_missing_issued_at",
+            ClaimsError::TooOld => "claims_too_old",
+            ClaimsError::InvalidIssuer => "claims_invalid_issuer",
+            ClaimsError::InvalidAudience => "claims_invalid_audience",
+        }
+    }
+}
+
+impl std::fmt::Display for ClaimsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ClaimsError::Expired => "Claims have expired",
+            ClaimsError::NotYetValid => "Claims are not yet valid",
+            ClaimsError::IssuedInFuture => "Claims were issued in the future",
+            ClaimsError::MissingIssuedAt => "Claims are missing the issued-at time",
+            ClaimsError::TooOld => "Claims were issued too long ago",
+            ClaimsError::InvalidIssuer => "Claims have an unexpected issuer",
+            ClaimsError::InvalidAudience => "Claims have an unexpected audience",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ClaimsError {}
+
+/// The validation of registered claims applied by `resolve` to decoded claims, regardless of the
+/// decoder. By default, checks `exp`, `nbf` and that `iat` is not in the future, with
+/// `DEFAULT_LEEWAY` for clock skew.
+#[derive(Clone, Debug)]
+pub struct ClaimsValidation {
+    issuer: Option<String>,
+    audience: Option<String>,
+    max_age: Option<Duration>,
+    leeway: Duration,
+}
+
+impl Default for ClaimsValidation {
+    fn default() -> Self {
+        ClaimsValidation::new()
+    }
+}
+
+impl ClaimsValidation {
+    pub const fn new() -> ClaimsValidation {
+        ClaimsValidation {
+            issuer: None,
+            audience: None,
+            max_age: None,
+            leeway: DEFAULT_LEEWAY,
+        }
+    }
+
+    /// The validation applied when a `SessionManager` doesn't provide its own.
+    pub fn default_ref() -> &'static ClaimsValidation {
+        &DEFAULT_CLAIMS_VALIDATION
+    }
+
+    /// Requires the `iss` claim to equal the issuer.
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Requires the `aud` claim to contain the audience.
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Requires the `iat` claim, no longer ago than the max age.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the allowed clock skew between the issuer and this server.
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    pub fn leeway(&self) -> Duration {
+        self.leeway
+    }
+
+    /// Validates the claims at a time, in seconds since the Unix epoch.
+    pub fn validate_at<C>(&self, claims: &SessionClaims<C>, now: u64) -> Result<(), ClaimsError> {
+        let leeway = self.leeway.as_secs();
+
+        if (claims.exp as u64).saturating_add(leeway) < now {
+            return Err(ClaimsError::Expired);
+        }
+
+        if let Some(nbf) = claims.nbf {
+            if nbf as u64 > now.saturating_add(leeway) {
+                return Err(ClaimsError::NotYetValid);
+            }
+        }
+
+        match (claims.iat, self.max_age) {
+            (Some(iat), _) if iat as u64 > now.saturating_add(leeway) => {
+                return Err(ClaimsError::IssuedInFuture);
+            }
+            (Some(iat), Some(max_age))
+                if (iat as u64)
+                    .saturating_add(max_age.as_secs())
+                    .saturating_add(leeway)
+                    < now =>
+            {
+                return Err(ClaimsError::TooOld);
+            }
+            (None, Some(_)) => return Err(ClaimsError::MissingIssuedAt),
+            _ => {}
+        }
+
+        if let Some(issuer) = &self.issuer {
+            if claims.iss.as_deref() != Some(issuer.as_str()) {
+                return Err(ClaimsError::InvalidIssuer);
+            }
+        }
+
+        if let Some(audience) = &self.audience {
+            if !claims
+                .aud
+                .as_ref()
+                .is_some_and(|aud| aud.contains(audience))
+            {
+                return Err(ClaimsError::InvalidAudience);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use hyper::StatusCode;
+
+use crate::session::session::{authorize, resolve, SessionClaims};
+use crate::session::validation::{Audience, ClaimsError, ClaimsValidation};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+const NOW: u64 = 1_700_000_000;
+
+fn claims() -> SessionClaims {
+    SessionClaims {
+        exp: (NOW + 600) as usize,
+        iat: Some(NOW as usize),
+        ..SessionClaims::new("test-account-id", Duration::ZERO, ()).unwrap()
+    }
+}
+
+#[test]
+fn test_expiry_and_not_before_allow_leeway() {
+    let validation = ClaimsValidation::new().with_leeway(Duration::from_secs(30));
+
+    assert_eq!(validation.validate_at(&claims(), NOW), Ok(()));
+    assert_eq!(validation.validate_at(&claims(), NOW + 620), Ok(()));
+    assert_eq!(
+        validation.validate_at(&claims(), NOW + 631),
+        Err(ClaimsError::Expired)
+    );
+
+    let not_yet = SessionClaims {
+        nbf: Some((NOW + 60) as usize),
+        ..claims()
+    };
+    assert_eq!(validation.validate_at(&not_yet, NOW + 30), Ok(()));
+    assert_eq!(
+        validation.validate_at(&not_yet, NOW + 29),
+        Err(ClaimsError::NotYetValid)
+    );
+}
+
+#[test]
+fn test_issued_at_bounds() {
+    let validation = ClaimsValidation::new()
+        .with_leeway(Duration::ZERO)
+        .with_max_age(Duration::from_secs(300));
+
+    assert_eq!(validation.validate_at(&claims(), NOW + 300), Ok(()));
+    assert_eq!(
+        validation.validate_at(&claims(), NOW + 301),
+        Err(ClaimsError::TooOld)
+    );
+    assert_eq!(
+        validation.validate_at(&claims(), NOW - 1),
+        Err(ClaimsError::IssuedInFuture)
+    );
+
+    let without_iat = SessionClaims {
+        iat: None,
+        ..claims()
+    };
+    assert_eq!(
+        validation.validate_at(&without_iat, NOW),
+        Err(ClaimsError::MissingIssuedAt)
+    );
+    assert_eq!(
+        ClaimsValidation::new().validate_at(&without_iat, NOW),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_issuer_and_audience() {
+    let validation = ClaimsValidation::new()
+        .with_issuer("https://auth.example.com")
+        .with_audience("api");
+
+    let claims = claims()
+        .with_issuer("https://auth.example.com")
+        .with_audience(Audience::Many(vec!["web".into(), "api".into()]));
+    assert_eq!(validation.validate_at(&claims, NOW), Ok(()));
+
+    let other_issuer = claims.clone().with_issuer("https://other.example.com");
+    assert_eq!(
+        validation.validate_at(&other_issuer, NOW),
+        Err(ClaimsError::InvalidIssuer)
+    );
+
+    let other_audience = claims.with_audience(Audience::One("web".into()));
+    assert_eq!(
+        validation.validate_at(&other_audience, NOW),
+        Err(ClaimsError::InvalidAudience)
+    );
+}
+
+#[test]
+fn test_audience_is_a_string_or_list() {
+    let one: Audience = serde_json::from_str(r#""api""#).unwrap();
+    let many: Audience = serde_json::from_str(r#"["web", "api"]"#).unwrap();
+
+    assert!(one.contains("api"));
+    assert!(many.contains("api"));
+    assert!(!many.contains("admin"));
+}
+
+#[derive(Clone)]
+struct FakeAccount {
+    name: String,
+}
+
+fn app(state: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|Extension(caller): Extension<FakeAccount>| async move { caller.name }),
+            )
+            .layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[tokio::test]
+async fn test_resolve_applies_validation_to_decoded_claims() {
+    let sessions = FakeSessionManager::new()
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+        .with_claims_validation(ClaimsValidation::new().with_audience("api"));
+    let client = app(sessions);
+
+    let session = |claims: SessionClaims| FakeSessionManager::<FakeAccount>::encode_claims(&claims);
+    let valid = || {
+        SessionClaims::new("test-account-id", Duration::from_secs(60), ())
+            .unwrap()
+            .with_audience(Audience::One("api".into()))
+    };
+
+    client
+        .get("/api/account")
+        .bearer(&session(valid()))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let expired = SessionClaims {
+        exp: SessionClaims::expires_in(Duration::ZERO).unwrap() - 120,
+        ..valid()
+    };
+    let not_yet = valid().with_not_before(Duration::from_secs(120)).unwrap();
+    let wrong_audience = valid().with_audience(Audience::One("web".into()));
+    for claims in [expired, not_yet, wrong_audience] {
+        client
+            .get("/api/account")
+            .bearer(&session(claims))
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+}
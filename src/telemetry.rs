@@ -0,0 +1,135 @@
+//! Crate-private instrumentation, emitting through `tracing` when the `tracing` feature is enabled
+//! and through the `log` facade otherwise.
+
+use std::fmt;
+use std::future::Future;
+
+use hyper::StatusCode;
+
+use crate::api::request_id::log_request_id;
+
+#[derive(Clone)]
+pub(crate) struct SessionSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl SessionSpan {
+    #[cfg(feature = "tracing")]
+    pub(crate) fn resolve(matched_path: Option<&str>) -> SessionSpan {
+        SessionSpan {
+            span: tracing::info_span!(
+                "resolve",
+                request_id = %log_request_id(),
+                matched_path = matched_path,
+                account_id = tracing::field::Empty,
+                outcome = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn resolve(_matched_path: Option<&str>) -> SessionSpan {
+        SessionSpan {}
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn authorize(matched_path: Option<&str>) -> SessionSpan {
+        SessionSpan {
+            span: tracing::info_span!(
+                "authorize",
+                request_id = %log_request_id(),
+                matched_path = matched_path,
+                outcome = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn authorize(_matched_path: Option<&str>) -> SessionSpan {
+        SessionSpan {}
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn record_account_id(&self, account_id: &str) {
+        self.span.record("account_id", account_id);
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn record_account_id(&self, _account_id: &str) {}
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn record_outcome(&self, outcome: &str) {
+        self.span.record("outcome", outcome);
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn record_outcome(&self, _outcome: &str) {}
+
+    #[cfg(feature = "tracing")]
+    pub(crate) async fn instrument<F: Future>(self, future: F) -> F::Output {
+        use tracing::Instrument;
+        future.instrument(self.span).await
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) async fn instrument<F: Future>(self, future: F) -> F::Output {
+        future.await
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn session_event(message: fmt::Arguments) {
+    tracing::info!(request_id = %log_request_id(), "{}", message);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn session_event(message: fmt::Arguments) {
+    log::info!("[request_id={}] {}", log_request_id(), message);
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn client_err(err: &anyhow::Error, code: StatusCode) {
+    tracing::info!(
+        request_id = %log_request_id(),
+        status = code.as_u16(),
+        error = %err,
+        error_chain = ?error_chain(err),
+        "Client error",
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn client_err(err: &anyhow::Error, code: StatusCode) {
+    log::info!(
+        "[request_id={}] Client error: {} as {}",
+        log_request_id(),
+        err,
+        code
+    );
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn internal_err(err: &anyhow::Error) {
+    tracing::error!(
+        request_id = %log_request_id(),
+        status = StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error = %err,
+        error_chain = ?error_chain(err),
+        "Internal error",
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn internal_err(err: &anyhow::Error) {
+    log::error!(
+        "[request_id={}] Internal error: {:?}",
+        log_request_id(),
+        err
+    );
+}
+
+#[cfg(feature = "tracing")]
+fn error_chain(err: &anyhow::Error) -> Vec<String> {
+    err.chain().map(|cause| cause.to_string()).collect()
+}
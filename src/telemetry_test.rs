@@ -0,0 +1,244 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use hyper::StatusCode;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+use crate::api::response::{JsonResponse, JsonResult};
+use crate::session::session::{authorize, resolve, SessionClaims};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+type Fields = HashMap<String, String>;
+
+/// Captures the fields of spans, by name, and events, for asserting on them.
+#[derive(Clone, Default)]
+struct Captured {
+    spans: Arc<Mutex<Vec<(String, Fields)>>>,
+    events: Arc<Mutex<Vec<Fields>>>,
+    entered: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Captured {
+    fn span(&self, name: &str) -> Fields {
+        let spans = self.spans.lock().unwrap();
+        let (_, fields) = spans.iter().rev().find(|(span, _)| span == name).unwrap();
+        fields.clone()
+    }
+
+    fn event(&self, message: &str) -> Fields {
+        let events = self.events.lock().unwrap();
+        events
+            .iter()
+            .find(|fields| fields.get("message").map(String::as_str) == Some(message))
+            .unwrap()
+            .clone()
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Captured {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut fields = Fields::new();
+        attributes.record(&mut FieldVisitor(&mut fields));
+
+        let mut spans = self.spans.lock().unwrap();
+        spans.push((attributes.metadata().name().into(), fields));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let (_, fields) = &mut spans[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        if let Some(span) = self.entered.lock().unwrap().last() {
+            let spans = self.spans.lock().unwrap();
+            fields.insert("span".into(), spans[*span as usize - 1].0.clone());
+        }
+        self.events.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+}
+
+#[derive(Clone)]
+struct FakeAccount;
+
+fn app(state: FakeSessionManager<FakeAccount>) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route(
+                "/api/account",
+                get(|_: Option<Extension<FakeAccount>>| async { "Hello!" }),
+            )
+            .route(
+                "/api/failing",
+                get(|| async {
+                    let result: JsonResult<()> = Err(anyhow::anyhow!("Connection refused")
+                        .context("Failed to load account")
+                        .into());
+                    result
+                }),
+            )
+            .route(
+                "/api/invalid",
+                get(|| async {
+                    JsonResponse::of_client_err(
+                        anyhow::anyhow!("Missing name"),
+                        StatusCode::BAD_REQUEST,
+                    )
+                }),
+            )
+            .route_layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    )
+}
+
+#[tokio::test]
+async fn test_resolve_span_records_path_account_and_outcome() {
+    let captured = Captured::default();
+    let _guard = tracing::subscriber::set_default(captured.clone());
+
+    let sessions = FakeSessionManager::new().with_account("test-account-id", FakeAccount);
+    let session = sessions.mint_session("test-account-id");
+    let client = app(sessions);
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let span = captured.span("resolve");
+    assert_eq!(span["matched_path"], "/api/account");
+    assert_eq!(span["account_id"], "test-account-id");
+    assert_eq!(span["outcome"], "resolved");
+}
+
+#[tokio::test]
+async fn test_resolve_span_omits_account_of_rejected_claims() {
+    let captured = Captured::default();
+    let _guard = tracing::subscriber::set_default(captured.clone());
+
+    let client = app(FakeSessionManager::new().with_account("test-account-id", FakeAccount));
+    let expired = SessionClaims {
+        exp: SessionClaims::expires_in(Duration::ZERO).unwrap() - 120,
+        ..SessionClaims::new("test-account-id", Duration::ZERO, ()).unwrap()
+    };
+
+    client
+        .get("/api/account")
+        .bearer(&FakeSessionManager::<FakeAccount>::encode_claims(&expired))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let span = captured.span("resolve");
+    assert_eq!(span["outcome"], "claims_expired");
+    assert!(!span.contains_key("account_id"));
+}
+
+#[tokio::test]
+async fn test_error_events_record_status_and_error_chain() {
+    let captured = Captured::default();
+    let _guard = tracing::subscriber::set_default(captured.clone());
+
+    let client = app(FakeSessionManager::new());
+
+    client
+        .get("/api/failing")
+        .send()
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let event = captured.event("Internal error");
+    assert_eq!(event["status"], "500");
+    assert_eq!(
+        event["error_chain"],
+        r#"["Failed to load account", "Connection refused"]"#
+    );
+
+    client
+        .get("/api/invalid")
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    let event = captured.event("Client error");
+    assert_eq!(event["status"], "400");
+    assert_eq!(event["error_chain"], r#"["Missing name"]"#);
+}
+
+#[tokio::test]
+async fn test_session_events_are_emitted_inside_their_spans() {
+    let captured = Captured::default();
+    let _guard = tracing::subscriber::set_default(captured.clone());
+
+    let state = FakeSessionManager::<FakeAccount>::new();
+    let client = TestClient::new(
+        Router::new()
+            .route("/api/protected", get(|| async { "Hello!" }))
+            .route_layer(from_fn_with_state(
+                state.clone(),
+                authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .route_layer(from_fn_with_state(
+                state.clone(),
+                resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+            ))
+            .with_state(state),
+    );
+
+    client
+        .get("/api/protected")
+        .send()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let event = captured.event("Account resolve skipped: No credential in request.");
+    assert_eq!(event["span"], "resolve");
+    assert!(event.contains_key("request_id"));
+
+    let event = captured.event("Unauthorized! Authentication was required.");
+    assert_eq!(event["span"], "authorize");
+    assert!(event.contains_key("request_id"));
+}
@@ -0,0 +1,189 @@
+use std::fmt::Debug;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, COOKIE},
+        request::Builder,
+        HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode,
+    },
+    Router,
+};
+use http_body_util::BodyExt;
+use serde::{de::DeserializeOwned, Serialize};
+use tower::ServiceExt;
+
+use crate::api::response::JsonStatus;
+use crate::session::session::Credential;
+
+/// Sends requests to a `Router` in-process, without binding a listener.
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+}
+
+impl TestClient {
+    pub fn new(router: Router) -> TestClient {
+        TestClient { router }
+    }
+
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest {
+        TestRequest {
+            router: self.router.clone(),
+            builder: Request::builder().method(method).uri(uri),
+            cookies: Vec::new(),
+            body: Body::empty(),
+        }
+    }
+
+    pub fn get(&self, uri: &str) -> TestRequest {
+        self.request(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> TestRequest {
+        self.request(Method::POST, uri)
+    }
+
+    pub fn put(&self, uri: &str) -> TestRequest {
+        self.request(Method::PUT, uri)
+    }
+
+    pub fn patch(&self, uri: &str) -> TestRequest {
+        self.request(Method::PATCH, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> TestRequest {
+        self.request(Method::DELETE, uri)
+    }
+}
+
+pub struct TestRequest {
+    router: Router,
+    builder: Builder,
+    cookies: Vec<String>,
+    body: Body,
+}
+
+impl TestRequest {
+    pub fn header<V>(mut self, key: HeaderName, value: V) -> Self
+    where
+        V: TryInto<HeaderValue>,
+        V::Error: Into<axum::http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// Sends the credential as a `Bearer` authorization header.
+    pub fn bearer(self, credential: &Credential) -> Self {
+        self.header(AUTHORIZATION, format!("Bearer {}", credential.0))
+    }
+
+    /// Sends the credential as a session cookie.
+    pub fn session_cookie(self, cookie_name: &str, credential: &Credential) -> Self {
+        self.cookie(cookie_name, &credential.0)
+    }
+
+    pub fn cookie(mut self, name: &str, value: &str) -> Self {
+        self.cookies.push(format!("{}={}", name, value));
+        self
+    }
+
+    pub fn json<B: Serialize>(mut self, body: &B) -> Self {
+        self.body = Body::from(serde_json::to_vec(body).expect("Failed to serialize body"));
+        self.header(CONTENT_TYPE, "application/json")
+    }
+
+    pub async fn send(mut self) -> TestResponse {
+        if !self.cookies.is_empty() {
+            self.builder = self.builder.header(COOKIE, self.cookies.join("; "));
+        }
+
+        let request = self
+            .builder
+            .body(self.body)
+            .expect("Failed to build request");
+        let response = self
+            .router
+            .oneshot(request)
+            .await
+            .expect("Router is infallible");
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("Failed to read body")
+            .to_bytes();
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// A response with its body collected, for making assertions.
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body)
+            .unwrap_or_else(|err| panic!("Failed to decode body {:?}: {}", self.text(), err))
+    }
+
+    pub fn assert_status(&self, code: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            code,
+            "Unexpected status, body: {}",
+            self.text()
+        );
+        self
+    }
+
+    /// Asserts the status, and a `JsonStatus` body for the status with the given detail. The `id`
+    /// of the body is not compared.
+    pub fn assert_status_body(&self, code: StatusCode, detail: Option<&str>) -> &Self {
+        self.assert_status(code);
+
+        let mut body: JsonStatus = self.json();
+        body.id = None;
+
+        assert_eq!(body, JsonStatus::of(code, detail.map(String::from)));
+        self
+    }
+
+    pub fn assert_json<T>(&self, code: StatusCode, expected: T) -> &Self
+    where
+        T: DeserializeOwned + PartialEq + Debug,
+    {
+        self.assert_status(code);
+        assert_eq!(self.json::<T>(), expected);
+        self
+    }
+}
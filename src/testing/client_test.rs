@@ -0,0 +1,102 @@
+use axum::middleware::from_fn_with_state;
+use axum::{routing::get, Extension, Router};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::api::response::{JsonResponse, JsonResult};
+use crate::session::session::{authorize, resolve};
+use crate::testing::client::TestClient;
+use crate::testing::session::FakeSessionManager;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct FakeAccount {
+    name: String,
+}
+
+fn app(state: FakeSessionManager<FakeAccount>) -> Router {
+    async fn handler(Extension(caller): Extension<FakeAccount>) -> JsonResult<FakeAccount> {
+        JsonResponse::of(caller).into()
+    }
+
+    Router::new()
+        .route("/api/account", get(handler))
+        .layer(from_fn_with_state(
+            state.clone(),
+            authorize::<FakeAccount, FakeSessionManager<FakeAccount>>,
+        ))
+        .layer(from_fn_with_state(
+            state.clone(),
+            resolve::<FakeAccount, FakeSessionManager<FakeAccount>>,
+        ))
+        .with_state(state)
+}
+
+fn fake_session_manager() -> FakeSessionManager<FakeAccount> {
+    FakeSessionManager::new()
+        .with_cookie("session")
+        .with_account(
+            "test-account-id",
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        )
+}
+
+#[tokio::test]
+async fn test_bearer_session_is_accepted() {
+    let sessions = fake_session_manager();
+    let session = sessions.mint_session("test-account-id");
+    let client = TestClient::new(app(sessions));
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_json(
+            StatusCode::OK,
+            FakeAccount {
+                name: "Test Account".into(),
+            },
+        );
+}
+
+#[tokio::test]
+async fn test_cookie_session_is_accepted() {
+    let sessions = fake_session_manager();
+    let session = sessions.mint_session("test-account-id");
+    let client = TestClient::new(app(sessions));
+
+    client
+        .get("/api/account")
+        .session_cookie("session", &session)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_removed_account_is_rejected() {
+    let sessions = fake_session_manager();
+    let session = sessions.mint_session("test-account-id");
+    sessions.remove_account("test-account-id");
+    let client = TestClient::new(app(sessions));
+
+    client
+        .get("/api/account")
+        .bearer(&session)
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+}
+
+#[tokio::test]
+async fn test_missing_session_is_rejected() {
+    let client = TestClient::new(app(fake_session_manager()));
+
+    client
+        .get("/api/account")
+        .send()
+        .await
+        .assert_status_body(StatusCode::UNAUTHORIZED, None);
+}
@@ -0,0 +1,4 @@
+pub mod client;
+#[cfg(test)]
+mod client_test;
+pub mod session;
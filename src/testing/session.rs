@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::extract::Request;
+use axum_extra::extract::CookieJar;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::session::clock::{Clock, SystemClock};
+use crate::session::impersonation::{ImpersonationAudit, MAX_IMPERSONATION_LIFETIME};
+use crate::session::session::{
+    matched_path, AccountStatus, Credential, SessionClaims, SessionManager,
+};
+use crate::session::tenant::{Tenant, TenantSource};
+use crate::session::validation::ClaimsValidation;
+
+/// A `SessionManager` for tests, holding accounts in memory and minting sessions as unsigned JSON
+/// claims. Never use it outside of tests.
+pub struct FakeSessionManager<U> {
+    accounts: Arc<RwLock<HashMap<String, (U, AccountStatus)>>>,
+    cookie_name: Option<String>,
+    unverified_routes: Vec<String>,
+    tenant_source: Option<TenantSource>,
+    impersonation_audits: Arc<RwLock<Vec<ImpersonationAudit>>>,
+    claims_validation: ClaimsValidation,
+    max_impersonation_lifetime: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl<U> Clone for FakeSessionManager<U> {
+    fn clone(&self) -> Self {
+        FakeSessionManager {
+            accounts: self.accounts.clone(),
+            cookie_name: self.cookie_name.clone(),
+            unverified_routes: self.unverified_routes.clone(),
+            tenant_source: self.tenant_source.clone(),
+            impersonation_audits: self.impersonation_audits.clone(),
+            claims_validation: self.claims_validation.clone(),
+            max_impersonation_lifetime: self.max_impersonation_lifetime,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<U> Default for FakeSessionManager<U> {
+    fn default() -> Self {
+        FakeSessionManager::new()
+    }
+}
+
+impl<U> FakeSessionManager<U> {
+    pub fn new() -> FakeSessionManager<U> {
+        FakeSessionManager {
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+            cookie_name: None,
+            unverified_routes: Vec::new(),
+            tenant_source: None,
+            impersonation_audits: Arc::new(RwLock::new(Vec::new())),
+            claims_validation: ClaimsValidation::new(),
+            max_impersonation_lifetime: MAX_IMPERSONATION_LIFETIME,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Also accepts the session from the named cookie, in addition to the `authorization` header.
+    pub fn with_cookie(mut self, cookie_name: &str) -> Self {
+        self.cookie_name = Some(cookie_name.into());
+        self
+    }
+
+    /// Allows unverified accounts on the route, by its matched path.
+    pub fn with_unverified_route(mut self, path: &str) -> Self {
+        self.unverified_routes.push(path.into());
+        self
+    }
+
+    /// Resolves the tenant of requests from the source. Accounts are shared across tenants.
+    pub fn with_tenant_source(mut self, tenant_source: TenantSource) -> Self {
+        self.tenant_source = Some(tenant_source);
+        self
+    }
+
+    pub fn with_claims_validation(mut self, claims_validation: ClaimsValidation) -> Self {
+        self.claims_validation = claims_validation;
+        self
+    }
+
+    pub fn with_max_impersonation_lifetime(mut self, lifetime: Duration) -> Self {
+        self.max_impersonation_lifetime = lifetime;
+        self
+    }
+
+    /// Mints and validates sessions by the clock, such as a `MockClock` to test expiry.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_account(self, account_id: &str, account: U) -> Self {
+        self.insert_account(account_id, account);
+        self
+    }
+
+    pub fn insert_account(&self, account_id: &str, account: U) {
+        self.insert_account_with_status(account_id, account, AccountStatus::Active);
+    }
+
+    pub fn insert_account_with_status(&self, account_id: &str, account: U, status: AccountStatus) {
+        self.accounts
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(account_id.into(), (account, status));
+    }
+
+    pub fn remove_account(&self, account_id: &str) {
+        self.accounts
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(account_id);
+    }
+
+    pub fn encode_claims<C: Serialize>(claims: &SessionClaims<C>) -> Credential {
+        Credential(serde_json::to_string(claims).expect("Failed to encode claims"))
+    }
+
+    /// Mints a session for the account, valid for an hour.
+    pub fn mint_session(&self, account_id: &str) -> Credential {
+        FakeSessionManager::<U>::encode_claims(
+            &SessionClaims::new_at(
+                self.clock.as_ref(),
+                account_id,
+                Duration::from_secs(3600),
+                (),
+            )
+            .expect("Failed to create claims"),
+        )
+    }
+
+    /// Mints a session for the account with custom claims, valid for an hour.
+    pub fn mint_session_with_claims<C: Serialize>(
+        &self,
+        account_id: &str,
+        custom: C,
+    ) -> Credential {
+        FakeSessionManager::<U>::encode_claims(
+            &SessionClaims::new_at(
+                self.clock.as_ref(),
+                account_id,
+                Duration::from_secs(3600),
+                custom,
+            )
+            .expect("Failed to create claims"),
+        )
+    }
+
+    /// Mints a session for the service account, valid for an hour.
+    pub fn mint_service_session(&self, account_id: &str) -> Credential {
+        FakeSessionManager::<U>::encode_claims(
+            &SessionClaims::new_service_at(
+                self.clock.as_ref(),
+                account_id,
+                Duration::from_secs(3600),
+            )
+            .expect("Failed to create claims"),
+        )
+    }
+
+    /// Mints a session for the actor to act as the account, valid for the maximum impersonation
+    /// lifetime.
+    pub fn mint_impersonation_session(&self, actor_id: &str, account_id: &str) -> Credential {
+        FakeSessionManager::<U>::encode_claims(
+            &SessionClaims::new_impersonation_at(
+                self.clock.as_ref(),
+                actor_id,
+                account_id,
+                MAX_IMPERSONATION_LIFETIME,
+            )
+            .expect("Failed to create claims"),
+        )
+    }
+
+    /// The impersonated requests audited so far.
+    pub fn impersonation_audits(&self) -> Vec<ImpersonationAudit> {
+        self.impersonation_audits
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Mints a session for the account in the tenant, valid for an hour.
+    pub fn mint_tenant_session(&self, tenant: &Tenant, account_id: &str) -> Credential {
+        FakeSessionManager::<U>::encode_claims(
+            &SessionClaims::new_at(
+                self.clock.as_ref(),
+                account_id,
+                Duration::from_secs(3600),
+                (),
+            )
+            .expect("Failed to create claims")
+            .with_tenant(tenant),
+        )
+    }
+}
+
+#[async_trait]
+impl<U, C> SessionManager<U, C> for FakeSessionManager<U>
+where
+    U: Clone + Send + Sync,
+    C: DeserializeOwned,
+{
+    async fn decode_claims(&self, credential: Credential) -> anyhow::Result<SessionClaims<C>> {
+        Ok(serde_json::from_str(&credential.0)?)
+    }
+
+    async fn get_account(&self, account_id: String) -> anyhow::Result<Option<U>> {
+        Ok(self
+            .accounts
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(&account_id)
+            .map(|(account, _)| account.clone()))
+    }
+
+    async fn get_account_status(
+        &self,
+        account_id: String,
+    ) -> anyhow::Result<Option<(U, AccountStatus)>>
+    where
+        U: Send,
+    {
+        Ok(self
+            .accounts
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(&account_id)
+            .cloned())
+    }
+
+    fn extract_credential(&self, request: &Request, cookies: &CookieJar) -> Option<Credential> {
+        Credential::from_authorization_header(request).or_else(|| {
+            self.cookie_name
+                .as_deref()
+                .and_then(|cookie_name| Credential::from_cookie(cookie_name, cookies))
+        })
+    }
+
+    async fn audit_impersonation(&self, audit: &ImpersonationAudit) -> anyhow::Result<()> {
+        self.impersonation_audits
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(audit.clone());
+        Ok(())
+    }
+
+    fn claims_validation(&self) -> &ClaimsValidation {
+        &self.claims_validation
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn max_impersonation_lifetime(&self) -> Duration {
+        self.max_impersonation_lifetime
+    }
+
+    fn resolve_tenant(&self, request: &Request) -> Option<Tenant> {
+        self.tenant_source
+            .as_ref()
+            .and_then(|tenant_source| tenant_source.resolve(request))
+    }
+
+    fn allows_unverified(&self, request: &Request) -> bool {
+        matched_path(request)
+            .is_some_and(|path| self.unverified_routes.iter().any(|route| route == path))
+    }
+}